@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    chain::bridge::GameEvent,
+    helpers::touch::Tap,
+    map::{cell_to_world, world_to_cell, Terrain, TILE_SIZE},
+    settlement::WorldObjects,
+    AppState,
+};
+
+/// Draw order of structures, above the route overlay and below the player.
+pub const STRUCTURE_Z: f32 = 0.8;
+
+/// The placement preview is drawn over structures already on the map.
+const GHOST_Z: f32 = 0.9;
+
+const GHOST_VALID: Color = Color::rgba(1.0, 1.0, 1.0, 0.6);
+const GHOST_INVALID: Color = Color::rgba(1.0, 0.3, 0.3, 0.6);
+/// Placed structures stay faded until the web app reports them on chain.
+const PENDING_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.6);
+
+const HOVERED_BORDER: Color = Color::WHITE;
+const SELECTED_BORDER: Color = Color::GOLD;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StructureKind {
+    House,
+    Farm,
+    Workshop,
+    Tower,
+    Keep,
+}
+
+impl StructureKind {
+    pub const ALL: [StructureKind; 5] = [
+        StructureKind::House,
+        StructureKind::Farm,
+        StructureKind::Workshop,
+        StructureKind::Tower,
+        StructureKind::Keep,
+    ];
+
+    pub fn texture(self) -> &'static str {
+        match self {
+            StructureKind::House => "Structure/medievalStructure_17.png",
+            StructureKind::Farm => "Structure/medievalStructure_19.png",
+            StructureKind::Workshop => "Structure/medievalStructure_21.png",
+            StructureKind::Tower => "Structure/medievalStructure_02.png",
+            StructureKind::Keep => "Structure/medievalStructure_06.png",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StructureKind::House => "House",
+            StructureKind::Farm => "Farm",
+            StructureKind::Workshop => "Workshop",
+            StructureKind::Tower => "Tower",
+            StructureKind::Keep => "Keep",
+        }
+    }
+}
+
+pub struct BuildPlugin;
+
+impl Plugin for BuildPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuildMode>()
+            .init_resource::<Settlements>()
+            .add_systems(Startup, spawn_toolbar)
+            .add_systems(
+                Update,
+                (
+                    select_structure,
+                    cancel_build,
+                    update_ghost,
+                    place_structure,
+                )
+                    .chain()
+                    .before(crate::player_movement)
+                    .run_if(in_state(AppState::Finished))
+                    .run_if(crate::round::round_open),
+            )
+            .add_systems(OnExit(AppState::Finished), stop_building)
+            .add_systems(OnEnter(AppState::Build), clear_settlements);
+    }
+}
+
+/// Structure the player is about to place, if any.
+#[derive(Resource, Default)]
+pub struct BuildMode {
+    selected: Option<StructureKind>,
+}
+
+/// Run condition for map clicks that mean something else while building.
+pub fn not_building(mode: Res<BuildMode>) -> bool {
+    mode.selected.is_none()
+}
+
+/// Structures placed on this map, waiting to be registered on chain, and their
+/// preview sprites.
+#[derive(Resource, Default)]
+pub struct Settlements {
+    tiles: HashMap<IVec2, (StructureKind, Entity)>,
+}
+
+impl Settlements {
+    pub fn get(&self, cell: IVec2) -> Option<StructureKind> {
+        self.tiles.get(&cell).map(|(kind, _)| *kind)
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.tiles.keys().copied()
+    }
+
+    /// Forgets the pending structure on `cell` and returns its sprite.
+    pub fn take(&mut self, cell: IVec2) -> Option<Entity> {
+        self.tiles.remove(&cell).map(|(_, entity)| entity)
+    }
+}
+
+#[derive(Component)]
+struct BuildButton(StructureKind);
+
+#[derive(Component)]
+struct Ghost;
+
+#[derive(Component)]
+struct PendingStructure;
+
+fn spawn_toolbar(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                bottom: Val::Px(10.0),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(6.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for kind in StructureKind::ALL {
+                parent.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(56.0),
+                            height: Val::Px(56.0),
+                            border: UiRect::all(Val::Px(3.0)),
+                            ..default()
+                        },
+                        border_color: BorderColor(Color::BLACK),
+                        image: UiImage::new(asset_server.load(kind.texture())),
+                        ..default()
+                    },
+                    BuildButton(kind),
+                ));
+            }
+        });
+}
+
+/// Toolbar clicks pick a structure, or put it back when it is already picked.
+fn select_structure(
+    mut mode: ResMut<BuildMode>,
+    mut buttons: Query<(Ref<Interaction>, &BuildButton, &mut BorderColor)>,
+) {
+    for (interaction, button, _) in &buttons {
+        if interaction.is_changed() && *interaction == Interaction::Pressed {
+            mode.selected = if mode.selected == Some(button.0) {
+                None
+            } else {
+                Some(button.0)
+            };
+        }
+    }
+
+    for (interaction, button, mut border) in &mut buttons {
+        let color = match *interaction {
+            _ if mode.selected == Some(button.0) => SELECTED_BORDER,
+            Interaction::Hovered | Interaction::Pressed => HOVERED_BORDER,
+            Interaction::None => Color::BLACK,
+        };
+        if border.0 != color {
+            border.0 = color;
+        }
+    }
+}
+
+fn cancel_build(
+    mut mode: ResMut<BuildMode>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+) {
+    if mode.selected.is_some()
+        && (keys.just_pressed(KeyCode::Escape) || mouse_buttons.just_pressed(MouseButton::Right))
+    {
+        mode.selected = None;
+    }
+}
+
+/// Tile under the cursor, unless the cursor is outside the window or over the UI.
+pub fn hovered_cell(
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    cameras: &Query<(&Camera, &GlobalTransform)>,
+    interactions: &Query<&Interaction>,
+) -> Option<IVec2> {
+    if interactions.iter().any(|i| *i != Interaction::None) {
+        return None;
+    }
+    let cursor = windows.get_single().ok()?.cursor_position()?;
+    screen_cell(cameras, cursor)
+}
+
+/// Tile shown at a screen position.
+fn screen_cell(cameras: &Query<(&Camera, &GlobalTransform)>, position: Vec2) -> Option<IVec2> {
+    cameras
+        .iter()
+        .find_map(|(camera, transform)| camera.viewport_to_world_2d(transform, position))
+        .map(world_to_cell)
+}
+
+fn can_build(
+    cell: IVec2,
+    terrain: &Terrain,
+    settlements: &Settlements,
+    world: &WorldObjects,
+) -> bool {
+    terrain.is_walkable(cell) && settlements.get(cell).is_none() && world.at(cell).is_none()
+}
+
+/// Keeps the translucent preview of the selected structure on the hovered tile,
+/// tinted red where it cannot be built.
+#[allow(clippy::too_many_arguments)]
+fn update_ghost(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mode: Res<BuildMode>,
+    terrain: Terrain,
+    settlements: Res<Settlements>,
+    world: Res<WorldObjects>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    interactions: Query<&Interaction>,
+    mut ghosts: Query<(Entity, &mut Transform, &mut Sprite, &mut Visibility), With<Ghost>>,
+) {
+    let Some(kind) = mode.selected else {
+        for (entity, ..) in &ghosts {
+            commands.entity(entity).despawn();
+        }
+        return;
+    };
+
+    if mode.is_changed() {
+        for (entity, ..) in &ghosts {
+            commands.entity(entity).despawn();
+        }
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: GHOST_VALID,
+                    custom_size: Some(Vec2::splat(TILE_SIZE)),
+                    ..default()
+                },
+                texture: asset_server.load(kind.texture()),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Ghost,
+        ));
+        return;
+    }
+
+    let cell = hovered_cell(&windows, &cameras, &interactions);
+    for (_, mut transform, mut sprite, mut visibility) in &mut ghosts {
+        let Some(cell) = cell else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Visible;
+        transform.translation = cell_to_world(cell).extend(GHOST_Z);
+        sprite.color = if can_build(cell, &terrain, &settlements, &world) {
+            GHOST_VALID
+        } else {
+            GHOST_INVALID
+        };
+    }
+}
+
+/// Left click or a tap confirms the placement and asks the web app to register it on
+/// chain.
+#[allow(clippy::too_many_arguments)]
+fn place_structure(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut mouse_buttons: ResMut<ButtonInput<MouseButton>>,
+    mut tap: ResMut<Tap>,
+    mut mode: ResMut<BuildMode>,
+    terrain: Terrain,
+    mut settlements: ResMut<Settlements>,
+    world: Res<WorldObjects>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    interactions: Query<&Interaction>,
+    mut game_events: EventWriter<GameEvent>,
+) {
+    let Some(kind) = mode.selected else {
+        return;
+    };
+    let cell = if mouse_buttons.just_pressed(MouseButton::Left) {
+        hovered_cell(&windows, &cameras, &interactions)
+    } else {
+        // Touch screens have no hover, so the tile is picked by the tap itself.
+        tap.position()
+            .and_then(|position| screen_cell(&cameras, position))
+    };
+    let Some(cell) = cell else {
+        return;
+    };
+    // The click is for the build, not for walking there.
+    mouse_buttons.clear_just_pressed(MouseButton::Left);
+    tap.consume();
+    if !can_build(cell, &terrain, &settlements, &world) {
+        info!("Cannot build a {:?} on tile {}", kind, cell);
+        return;
+    }
+
+    let pending = commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: PENDING_COLOR,
+                custom_size: Some(Vec2::splat(TILE_SIZE)),
+                ..default()
+            },
+            texture: asset_server.load(kind.texture()),
+            transform: Transform::from_translation(cell_to_world(cell).extend(STRUCTURE_Z)),
+            ..default()
+        },
+        PendingStructure,
+    ));
+    settlements.tiles.insert(cell, (kind, pending.id()));
+    game_events.send(GameEvent::StructurePlaced {
+        structure: kind,
+        x: cell.x,
+        y: cell.y,
+    });
+    mode.selected = None;
+}
+
+fn stop_building(
+    mut commands: Commands,
+    mut mode: ResMut<BuildMode>,
+    ghosts: Query<Entity, With<Ghost>>,
+) {
+    mode.selected = None;
+    for entity in &ghosts {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// A new map starts without settlements.
+fn clear_settlements(
+    mut commands: Commands,
+    mut settlements: ResMut<Settlements>,
+    pending: Query<Entity, With<PendingStructure>>,
+) {
+    settlements.tiles.clear();
+    for entity in &pending {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,515 @@
+//! Versioned bridge between the web app and the game.
+//!
+//! JS sends `{ version, command }` objects through `send_command`. Commands are checked
+//! and deserialized at the wasm boundary, so malformed input is reported back to JS
+//! as an error, then queued on a channel that `drain_commands` turns into Bevy events
+//! at the start of every frame.
+//!
+//! The other way round, systems send `GameEvent`s, which are copied into an outbound
+//! queue at the end of the frame and serialized when JS collects them with
+//! `take_game_events`.
+//!
+//! State the web app reads on demand rather than per event, like the explored tiles,
+//! is published into a static by the owning system and returned by its `export_*`
+//! function.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Mutex, OnceLock,
+    },
+};
+
+use bevy::prelude::*;
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::{build::StructureKind, map::explored::Explored, settlement::WorldObject};
+
+use super::{
+    ledger::{Player, Victory},
+    victory::{self, Activity},
+};
+
+/// Bumped whenever a command or event changes shape in a way old web builds cannot
+/// handle.
+pub const BRIDGE_VERSION: u32 = 1;
+
+/// Outbound events kept while JS is not collecting them; the oldest are dropped first.
+const MAX_PENDING_EVENTS: usize = 1024;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+pub enum BridgeCommand {
+    /// Seed of the map, taken from the on-chain `Game` object.
+    SetMapSeed { seed: u32 },
+    /// Replaces one victory leaderboard.
+    SetLeaderboard {
+        victory: Victory,
+        players: Vec<Player>,
+    },
+    /// Sui address of the signed-in player.
+    SetLocalPlayer { address: String },
+    /// Raw on-chain activity of every player; all three leaderboards are scored
+    /// from it by `victory`.
+    SetActivity { players: Vec<Activity> },
+    /// Start and end of the current game round, both in epochs or both in
+    /// milliseconds since the Unix epoch.
+    SetRound {
+        round: u32,
+        start: RoundTime,
+        end: RoundTime,
+    },
+    /// Current Sui epoch and checkpoint timestamp; the game counts on from there
+    /// until the next update.
+    SetClock { epoch: u64, timestamp_ms: u64 },
+    /// Every structure registered on chain; replaces the previous list.
+    SetWorldObjects { objects: Vec<WorldObject> },
+    /// Player addresses in the on-chain `Registry`; replaces the previous list.
+    SetRoster { addresses: Vec<String> },
+    /// Tiles the local player explored before, as returned by `export_explored`.
+    SetExplored {
+        #[serde(deserialize_with = "deserialize_explored")]
+        data: Explored,
+    },
+}
+
+/// Reads a `Uint8Array`, which serde-wasm-bindgen hands over as bytes, or a plain
+/// array of numbers. A bare `Vec<u8>` only accepts the latter once an internally
+/// tagged enum like `BridgeCommand` has buffered it.
+fn deserialize_bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    struct BytesVisitor;
+
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a Uint8Array or an array of bytes")
+        }
+
+        fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Vec<u8>, E> {
+            Ok(bytes.to_vec())
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, bytes: Vec<u8>) -> Result<Vec<u8>, E> {
+            Ok(bytes)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+            while let Some(byte) = seq.next_element()? {
+                bytes.push(byte);
+            }
+            Ok(bytes)
+        }
+    }
+
+    deserializer.deserialize_byte_buf(BytesVisitor)
+}
+
+/// Decodes explored tiles while the command is deserialized, so malformed data is
+/// reported to JS and the game only ever sees valid sets.
+fn deserialize_explored<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Explored, D::Error> {
+    let bytes = deserialize_bytes(deserializer)?;
+    Explored::from_bytes(&bytes).map_err(de::Error::custom)
+}
+
+impl BridgeCommand {
+    /// Checks what the types alone cannot express.
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            BridgeCommand::SetRound { start, end, .. } => match (start, end) {
+                (RoundTime::Epoch(start), RoundTime::Epoch(end))
+                | (RoundTime::TimestampMs(start), RoundTime::TimestampMs(end)) => {
+                    if end <= start {
+                        return Err(format!("round ends at {end}, before it starts at {start}"));
+                    }
+                    Ok(())
+                }
+                _ => Err("round start and end must both be epochs or both timestamps".into()),
+            },
+            BridgeCommand::SetWorldObjects { objects } => {
+                let mut ids = HashSet::new();
+                let mut tiles = HashSet::new();
+                for (i, object) in objects.iter().enumerate() {
+                    if !ids.insert(&object.id) {
+                        return Err(format!("object {} is listed twice", object.id));
+                    }
+                    if !tiles.insert(object.cell()) {
+                        return Err(format!(
+                            "object {} at index {i} shares tile {} with another object",
+                            object.id,
+                            object.cell()
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A point in chain time: `{ epoch: 12 }` or `{ timestamp_ms: 1718000000000 }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundTime {
+    Epoch(u64),
+    TimestampMs(u64),
+}
+
+#[derive(Deserialize)]
+struct Envelope {
+    version: u32,
+    command: BridgeCommand,
+}
+
+/// Something the player did that the web app may want to act on, e.g. by building
+/// and signing a transaction.
+#[derive(Event, Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    TileClicked {
+        x: i32,
+        y: i32,
+    },
+    LedgerOpened,
+    LedgerClosed,
+    RoundEnded {
+        round: u32,
+    },
+    /// The player confirmed a structure in build mode; the web app registers it on chain.
+    StructurePlaced {
+        structure: StructureKind,
+        x: i32,
+        y: i32,
+    },
+}
+
+#[derive(Serialize)]
+struct OutboundEnvelope<'a> {
+    version: u32,
+    event: &'a GameEvent,
+}
+
+#[derive(Event)]
+pub struct MapSeedReceived(pub u32);
+
+#[derive(Event)]
+pub struct LeaderboardReceived {
+    pub victory: Victory,
+    pub players: Vec<Player>,
+}
+
+#[derive(Event)]
+pub struct LocalPlayerReceived(pub String);
+
+#[derive(Event)]
+pub struct RoundReceived {
+    pub round: u32,
+    pub start: RoundTime,
+    pub end: RoundTime,
+}
+
+#[derive(Event)]
+pub struct WorldObjectsReceived(pub Vec<WorldObject>);
+
+#[derive(Event)]
+pub struct RosterReceived(pub Vec<String>);
+
+#[derive(Event)]
+pub struct ExploredReceived(pub Explored);
+
+#[derive(Event)]
+pub struct ClockReceived {
+    pub epoch: u64,
+    pub timestamp_ms: u64,
+}
+
+pub struct BridgePlugin;
+
+impl Plugin for BridgePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MapSeedReceived>()
+            .add_event::<LeaderboardReceived>()
+            .add_event::<LocalPlayerReceived>()
+            .add_event::<RoundReceived>()
+            .add_event::<ClockReceived>()
+            .add_event::<WorldObjectsReceived>()
+            .add_event::<RosterReceived>()
+            .add_event::<ExploredReceived>()
+            .add_event::<GameEvent>()
+            .add_systems(PreUpdate, drain_commands)
+            .add_systems(PostUpdate, forward_events);
+    }
+}
+
+type Channel = (Sender<BridgeCommand>, Mutex<Receiver<BridgeCommand>>);
+
+fn channel() -> &'static Channel {
+    static CHANNEL: OnceLock<Channel> = OnceLock::new();
+    CHANNEL.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        (sender, Mutex::new(receiver))
+    })
+}
+
+fn outbound() -> &'static Mutex<VecDeque<GameEvent>> {
+    static OUTBOUND: OnceLock<Mutex<VecDeque<GameEvent>>> = OnceLock::new();
+    OUTBOUND.get_or_init(Default::default)
+}
+
+fn explored_tiles() -> &'static Mutex<Vec<u8>> {
+    static EXPLORED: OnceLock<Mutex<Vec<u8>>> = OnceLock::new();
+    EXPLORED.get_or_init(|| Mutex::new(Explored::default().to_bytes()))
+}
+
+/// Makes `explored` what the next `export_explored` call returns.
+pub(crate) fn publish_explored(explored: &Explored) {
+    if let Ok(mut bytes) = explored_tiles().lock() {
+        *bytes = explored.to_bytes();
+    }
+}
+
+pub(crate) fn enqueue(command: BridgeCommand) {
+    // The receiver lives in a static, so sending cannot fail.
+    let _ = channel().0.send(command);
+}
+
+/// Version of the command format this build understands.
+#[wasm_bindgen]
+pub fn bridge_version() -> u32 {
+    BRIDGE_VERSION
+}
+
+/// Queues a command for the game, e.g.
+/// `send_command({ version: 1, command: { type: "set_map_seed", seed: 42 } })`.
+#[wasm_bindgen]
+pub fn send_command(command: JsValue) -> Result<(), JsError> {
+    let envelope: Envelope = serde_wasm_bindgen::from_value(command)
+        .map_err(|e| JsError::new(&format!("invalid command: {e}")))?;
+
+    if envelope.version != BRIDGE_VERSION {
+        return Err(JsError::new(&format!(
+            "unsupported bridge version {}, this game expects {}",
+            envelope.version, BRIDGE_VERSION
+        )));
+    }
+
+    submit(envelope.command)
+}
+
+/// Replaces the structures shown on the map, e.g.
+/// `set_world_objects([{ id, owner, structure: "house", x: 3, y: -2, level: 1 }])`.
+/// Shorthand for the `set_world_objects` command.
+#[wasm_bindgen]
+pub fn set_world_objects(objects: JsValue) -> Result<(), JsError> {
+    let objects = serde_wasm_bindgen::from_value(objects)
+        .map_err(|e| JsError::new(&format!("invalid world objects: {e}")))?;
+    submit(BridgeCommand::SetWorldObjects { objects })
+}
+
+/// Restores the tiles the local player explored, e.g. from a save made with
+/// `export_explored`. Shorthand for the `set_explored` command.
+#[wasm_bindgen]
+pub fn import_explored(data: &[u8]) -> Result<(), JsError> {
+    let data = Explored::from_bytes(data)
+        .map_err(|e| JsError::new(&format!("invalid explored tiles: {e}")))?;
+    submit(BridgeCommand::SetExplored { data })
+}
+
+/// Tiles the local player has explored on the current map, encoded as described in
+/// `map::explored`, for the web app to persist.
+#[wasm_bindgen]
+pub fn export_explored() -> Vec<u8> {
+    explored_tiles()
+        .lock()
+        .map(|bytes| bytes.clone())
+        .unwrap_or_default()
+}
+
+fn submit(command: BridgeCommand) -> Result<(), JsError> {
+    command
+        .validate()
+        .map_err(|e| JsError::new(&format!("invalid command: {e}")))?;
+
+    enqueue(command);
+    Ok(())
+}
+
+/// Returns and clears the `{ version, event }` objects sent since the last call.
+/// Meant to be polled once per animation frame.
+#[wasm_bindgen]
+pub fn take_game_events() -> Result<Vec<JsValue>, JsError> {
+    let Ok(mut queue) = outbound().lock() else {
+        return Ok(Vec::new());
+    };
+
+    queue
+        .drain(..)
+        .map(|event| {
+            let envelope = OutboundEnvelope {
+                version: BRIDGE_VERSION,
+                event: &event,
+            };
+            serde_wasm_bindgen::to_value(&envelope)
+                .map_err(|e| JsError::new(&format!("could not serialize {event:?}: {e}")))
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn drain_commands(
+    mut seeds: EventWriter<MapSeedReceived>,
+    mut leaderboards: EventWriter<LeaderboardReceived>,
+    mut local_players: EventWriter<LocalPlayerReceived>,
+    mut rounds: EventWriter<RoundReceived>,
+    mut clocks: EventWriter<ClockReceived>,
+    mut world_objects: EventWriter<WorldObjectsReceived>,
+    mut rosters: EventWriter<RosterReceived>,
+    mut explored: EventWriter<ExploredReceived>,
+) {
+    let Ok(receiver) = channel().1.lock() else {
+        return;
+    };
+
+    for command in receiver.try_iter() {
+        match command {
+            BridgeCommand::SetMapSeed { seed } => {
+                seeds.send(MapSeedReceived(seed));
+            }
+            BridgeCommand::SetLeaderboard { victory, players } => {
+                leaderboards.send(LeaderboardReceived { victory, players });
+            }
+            BridgeCommand::SetLocalPlayer { address } => {
+                local_players.send(LocalPlayerReceived(address));
+            }
+            BridgeCommand::SetActivity { players } => {
+                for victory in Victory::ALL {
+                    leaderboards.send(LeaderboardReceived {
+                        victory,
+                        players: victory::leaderboard(victory, &players),
+                    });
+                }
+            }
+            BridgeCommand::SetRound { round, start, end } => {
+                rounds.send(RoundReceived { round, start, end });
+            }
+            BridgeCommand::SetClock {
+                epoch,
+                timestamp_ms,
+            } => {
+                clocks.send(ClockReceived {
+                    epoch,
+                    timestamp_ms,
+                });
+            }
+            BridgeCommand::SetWorldObjects { objects } => {
+                world_objects.send(WorldObjectsReceived(objects));
+            }
+            BridgeCommand::SetRoster { addresses } => {
+                rosters.send(RosterReceived(addresses));
+            }
+            BridgeCommand::SetExplored { data } => {
+                explored.send(ExploredReceived(data));
+            }
+        }
+    }
+}
+
+fn forward_events(mut events: EventReader<GameEvent>) {
+    let Ok(mut queue) = outbound().lock() else {
+        return;
+    };
+
+    for event in events.read() {
+        queue.push_back(event.clone());
+        if queue.len() > MAX_PENDING_EVENTS {
+            queue.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::{
+        value::{Error, MapDeserializer, SeqDeserializer},
+        IntoDeserializer,
+    };
+
+    use super::*;
+
+    /// Values as serde-wasm-bindgen passes them on: a `Uint8Array` arrives as bytes,
+    /// a JS array of numbers as a sequence.
+    enum JsInput {
+        Str(&'static str),
+        Bytes(Vec<u8>),
+        Array(Vec<u8>),
+    }
+
+    impl<'de> IntoDeserializer<'de, Error> for JsInput {
+        type Deserializer = Self;
+
+        fn into_deserializer(self) -> Self {
+            self
+        }
+    }
+
+    impl<'de> Deserializer<'de> for JsInput {
+        type Error = Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self {
+                JsInput::Str(value) => visitor.visit_str(value),
+                JsInput::Bytes(bytes) => visitor.visit_byte_buf(bytes),
+                JsInput::Array(bytes) => visitor.visit_seq(SeqDeserializer::new(bytes.into_iter())),
+            }
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+            byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map
+            struct enum identifier ignored_any
+        }
+    }
+
+    fn explored_command(data: JsInput) -> Result<Explored, Error> {
+        let fields = vec![("type", JsInput::Str("set_explored")), ("data", data)];
+        match BridgeCommand::deserialize(MapDeserializer::<_, Error>::new(fields.into_iter()))? {
+            BridgeCommand::SetExplored { data } => Ok(data),
+            command => panic!("not a set_explored command: {command:?}"),
+        }
+    }
+
+    fn explored() -> Explored {
+        let mut explored = Explored::default();
+        explored.reveal((-3, 20), 4);
+        explored
+    }
+
+    #[test]
+    fn explored_data_accepts_uint8_arrays() {
+        let data = explored_command(JsInput::Bytes(explored().to_bytes()));
+        assert_eq!(data, Ok(explored()));
+    }
+
+    #[test]
+    fn explored_data_accepts_number_arrays() {
+        let data = explored_command(JsInput::Array(explored().to_bytes()));
+        assert_eq!(data, Ok(explored()));
+    }
+
+    #[test]
+    fn malformed_explored_data_is_rejected() {
+        let mut bytes = explored().to_bytes();
+        bytes.pop();
+        let error = explored_command(JsInput::Bytes(bytes)).expect_err("truncated data");
+        assert!(error.to_string().contains("middle of the chunk"), "{error}");
+    }
+}
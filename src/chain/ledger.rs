@@ -1,53 +1,144 @@
-use std::cell::RefCell;
+use std::cmp::Reverse;
 
-use serde::{Serialize, Deserialize};
-use wasm_bindgen::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+use super::{
+    bridge::{LeaderboardReceived, LocalPlayerReceived},
+    score::Score,
+    victory::normalize_address,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
     pub address: String,
-    pub score: String,
+    pub score: Score,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Victory {
+    #[default]
+    Economic,
+    Cultural,
+    Diplomatic,
+}
+
+impl Victory {
+    pub const ALL: [Victory; 3] = [Victory::Economic, Victory::Cultural, Victory::Diplomatic];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Victory::Economic => "Economic Victory",
+            Victory::Cultural => "Cultural Victory",
+            Victory::Diplomatic => "Diplomatic Victory",
+        }
+    }
 }
 
-thread_local!(pub static ECONOMIC_VICTORY: RefCell<Vec<Player>> = RefCell::new(Vec::new()));
-thread_local!(pub static CULTURAL_VICTORY: RefCell<Vec<Player>> = RefCell::new(Vec::new()));
-thread_local!(pub static DIPLOMATIC_VICTORY: RefCell<Vec<Player>> = RefCell::new(Vec::new()));
+/// Latest leaderboards pushed by the web app.
+#[derive(Resource, Default)]
+pub struct Leaderboards {
+    pub economic: Vec<Player>,
+    pub cultural: Vec<Player>,
+    pub diplomatic: Vec<Player>,
+}
 
-#[wasm_bindgen]
-pub fn set_economic_victory_leaderboard(players: Vec<JsValue>) {
-    ECONOMIC_VICTORY.with(|s| {
-        let mut res = Vec::new();
-        for player in players {
-            let p: Player = serde_wasm_bindgen::from_value(player).expect("parse player");
-            res.push(p)
+impl Leaderboards {
+    pub fn get(&self, victory: Victory) -> &[Player] {
+        match victory {
+            Victory::Economic => &self.economic,
+            Victory::Cultural => &self.cultural,
+            Victory::Diplomatic => &self.diplomatic,
         }
-        
-        *s.borrow_mut() = res;
-    });
-}
-
-#[wasm_bindgen]
-pub fn set_cultural_victory_leaderboard(players: Vec<JsValue>) {
-    CULTURAL_VICTORY.with(|s| {
-        let mut res = Vec::new();
-        for player in players {
-            let p: Player = serde_wasm_bindgen::from_value(player).expect("parse player");
-            res.push(p)
+    }
+
+    /// Players of one leaderboard from highest to lowest score, with their rank.
+    /// Equal scores share a rank.
+    pub fn ranked(&self, victory: Victory) -> Vec<(usize, &Player)> {
+        let mut players: Vec<&Player> = self.get(victory).iter().collect();
+        players.sort_by_key(|player| Reverse(player.score));
+
+        let mut ranked: Vec<(usize, &Player)> = Vec::with_capacity(players.len());
+        for (i, player) in players.into_iter().enumerate() {
+            let rank = match ranked.last() {
+                Some((rank, previous)) if previous.score == player.score => *rank,
+                _ => i + 1,
+            };
+            ranked.push((rank, player));
         }
-        
-        *s.borrow_mut() = res;
-    });
-}
-
-#[wasm_bindgen]
-pub fn set_diplomatic_victory_leaderboard(players: Vec<JsValue>) {
-    DIPLOMATIC_VICTORY.with(|s| {
-        let mut res = Vec::new();
-        for player in players {
-            let p: Player = serde_wasm_bindgen::from_value(player).expect("parse player");
-            res.push(p)
+        ranked
+    }
+
+    fn get_mut(&mut self, victory: Victory) -> &mut Vec<Player> {
+        match victory {
+            Victory::Economic => &mut self.economic,
+            Victory::Cultural => &mut self.cultural,
+            Victory::Diplomatic => &mut self.diplomatic,
         }
-        
-        *s.borrow_mut() = res;
-    });
-}
\ No newline at end of file
+    }
+}
+
+/// Sui address of the signed-in player, once the web app has told us; kept in the
+/// form `normalize_address` gives.
+#[derive(Resource, Default)]
+pub struct LocalPlayer(pub Option<String>);
+
+impl LocalPlayer {
+    /// Whether `address` is the local player's, however either of them is spelled.
+    pub fn is(&self, address: &str) -> bool {
+        self.0
+            .as_deref()
+            .is_some_and(|local| normalize_address(local) == normalize_address(address))
+    }
+}
+
+/// `0x1234…cdef` form of a Sui address for tight spaces.
+pub fn shorten_address(address: &str) -> String {
+    if address.len() <= 12 || !address.is_ascii() {
+        return address.to_string();
+    }
+    format!("{}…{}", &address[..6], &address[address.len() - 4..])
+}
+
+pub struct LedgerPlugin;
+
+impl Plugin for LedgerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Leaderboards>()
+            .init_resource::<LocalPlayer>()
+            .add_systems(Update, (update_leaderboards, update_local_player));
+    }
+}
+
+fn update_leaderboards(
+    mut events: EventReader<LeaderboardReceived>,
+    mut leaderboards: ResMut<Leaderboards>,
+) {
+    for event in events.read() {
+        *leaderboards.get_mut(event.victory) = event.players.clone();
+    }
+}
+
+fn update_local_player(
+    mut events: EventReader<LocalPlayerReceived>,
+    mut local_player: ResMut<LocalPlayer>,
+) {
+    if let Some(LocalPlayerReceived(address)) = events.read().last() {
+        local_player.0 = Some(normalize_address(address));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_player_matches_any_spelling() {
+        let local = LocalPlayer(Some("0x2".to_string()));
+        assert!(local.is(&format!("0x{:0>64}", "2")));
+        assert!(local.is("0X02"));
+        assert!(!local.is("0x3"));
+        assert!(!LocalPlayer::default().is("0x2"));
+    }
+}
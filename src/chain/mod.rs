@@ -1,15 +1,47 @@
+pub mod bridge;
 pub mod ledger;
+pub mod score;
 pub mod utils;
-use std::cell::RefCell;
+pub mod victory;
 
 use wasm_bindgen::prelude::*;
 
-thread_local!(pub static ONCHAIN_MAP_SEED: RefCell<u32> = RefCell::new(0));
+use bridge::{enqueue, BridgeCommand};
+use ledger::Victory;
 
+// Entry points from before `bridge::send_command`, kept for existing web builds. Each
+// queues the matching command; new code should send the command instead.
 
 #[wasm_bindgen]
 pub fn set_map_randomness(seed: u32) {
-    ONCHAIN_MAP_SEED.with(|s| {
-        *s.borrow_mut() = seed;
-    });
+    enqueue(BridgeCommand::SetMapSeed { seed });
+}
+
+fn set_leaderboard(victory: Victory, players: Vec<JsValue>) -> Result<(), JsError> {
+    let players = players
+        .into_iter()
+        .enumerate()
+        .map(|(i, player)| {
+            serde_wasm_bindgen::from_value(player)
+                .map_err(|e| JsError::new(&format!("invalid player at index {i}: {e}")))
+        })
+        .collect::<Result<_, _>>()?;
+
+    enqueue(BridgeCommand::SetLeaderboard { victory, players });
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn set_economic_victory_leaderboard(players: Vec<JsValue>) -> Result<(), JsError> {
+    set_leaderboard(Victory::Economic, players)
+}
+
+#[wasm_bindgen]
+pub fn set_cultural_victory_leaderboard(players: Vec<JsValue>) -> Result<(), JsError> {
+    set_leaderboard(Victory::Cultural, players)
+}
+
+#[wasm_bindgen]
+pub fn set_diplomatic_victory_leaderboard(players: Vec<JsValue>) -> Result<(), JsError> {
+    set_leaderboard(Victory::Diplomatic, players)
 }
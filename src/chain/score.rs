@@ -0,0 +1,349 @@
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use serde::{
+    de::{self, MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// Decimal places of a SUI amount; one SUI is 10^9 MIST.
+pub const SUI_DECIMALS: u8 = 9;
+
+/// Most decimal places a score may carry.
+pub const MAX_DECIMALS: u8 = 18;
+
+/// Fixed-point leaderboard score: `amount / 10^decimals`.
+///
+/// Scores compare by value, so `1.5` and `1.50` are equal whatever their scale.
+/// The web app sends them as decimal strings (`"-12.5"`), safe integers, or
+/// `{ amount, decimals }` objects for raw on-chain amounts such as MIST.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Score {
+    amount: i128,
+    decimals: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScoreError {
+    Empty,
+    InvalidCharacter { input: String, character: char },
+    TooManyDecimals { input: String, decimals: usize },
+    Overflow { input: String },
+    InexactNumber(f64),
+}
+
+impl fmt::Display for ScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScoreError::Empty => write!(f, "score is empty"),
+            ScoreError::InvalidCharacter { input, character } => {
+                write!(f, "score {input:?} contains unexpected character {character:?}")
+            }
+            ScoreError::TooManyDecimals { input, decimals } => write!(
+                f,
+                "score {input:?} has {decimals} decimal places, at most {MAX_DECIMALS} are supported"
+            ),
+            ScoreError::Overflow { input } => {
+                write!(f, "score {input:?} does not fit in a signed 128-bit integer")
+            }
+            ScoreError::InexactNumber(value) => write!(
+                f,
+                "score {value} is not a safe integer; send fractional or large scores as strings"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScoreError {}
+
+impl Score {
+    pub fn new(amount: i128, decimals: u8) -> Result<Self, ScoreError> {
+        if decimals > MAX_DECIMALS {
+            return Err(ScoreError::TooManyDecimals {
+                input: amount.to_string(),
+                decimals: decimals.into(),
+            });
+        }
+        Ok(Score { amount, decimals })
+    }
+
+    pub fn from_mist(mist: i128) -> Self {
+        Score {
+            amount: mist,
+            decimals: SUI_DECIMALS,
+        }
+    }
+
+    /// Whole and fractional parts, the latter scaled to `MAX_DECIMALS` places.
+    /// Both carry the sign of the score, so the pair orders like the value.
+    fn parts(&self) -> (i128, i128) {
+        let scale = 10i128.pow(self.decimals.into());
+        let fraction = self.amount % scale * 10i128.pow((MAX_DECIMALS - self.decimals).into());
+        (self.amount / scale, fraction)
+    }
+}
+
+impl From<u64> for Score {
+    fn from(value: u64) -> Self {
+        Score {
+            amount: value.into(),
+            decimals: 0,
+        }
+    }
+}
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.parts().cmp(&other.parts())
+    }
+}
+
+impl FromStr for Score {
+    type Err = ScoreError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let trimmed = input.trim();
+        let (negative, digits) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+        };
+        if digits.is_empty() {
+            return Err(ScoreError::Empty);
+        }
+
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(ScoreError::Empty);
+        }
+        if let Some(character) = whole
+            .chars()
+            .chain(fraction.chars())
+            .find(|c| !c.is_ascii_digit())
+        {
+            return Err(ScoreError::InvalidCharacter {
+                input: input.to_string(),
+                character,
+            });
+        }
+        if fraction.len() > MAX_DECIMALS.into() {
+            return Err(ScoreError::TooManyDecimals {
+                input: input.to_string(),
+                decimals: fraction.len(),
+            });
+        }
+
+        let overflow = || ScoreError::Overflow {
+            input: input.to_string(),
+        };
+        let mut amount: i128 = 0;
+        for digit in whole.bytes().chain(fraction.bytes()) {
+            let digit = i128::from(digit - b'0');
+            amount = amount
+                .checked_mul(10)
+                .and_then(|a| {
+                    if negative {
+                        a.checked_sub(digit)
+                    } else {
+                        a.checked_add(digit)
+                    }
+                })
+                .ok_or_else(overflow)?;
+        }
+
+        Ok(Score {
+            amount,
+            decimals: fraction.len() as u8,
+        })
+    }
+}
+
+/// Plain decimal notation without trailing zeros, e.g. `1.5` for 1 500 000 000 MIST.
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.amount.unsigned_abs().to_string();
+        let decimals = usize::from(self.decimals);
+        let sign = if self.amount < 0 { "-" } else { "" };
+
+        if decimals == 0 {
+            return f.pad(&format!("{sign}{digits}"));
+        }
+
+        let digits = format!("{digits:0>width$}", width = decimals + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - decimals);
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            f.pad(&format!("{sign}{whole}"))
+        } else {
+            f.pad(&format!("{sign}{whole}.{fraction}"))
+        }
+    }
+}
+
+impl Serialize for Score {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Score {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ScoreVisitor)
+    }
+}
+
+struct ScoreVisitor;
+
+/// Largest integer a JS number holds exactly.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+impl<'de> Visitor<'de> for ScoreVisitor {
+    type Value = Score;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a decimal string, an integer or an { amount, decimals } object")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Score, E> {
+        value.parse().map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Score, E> {
+        Ok(Score {
+            amount: value.into(),
+            decimals: 0,
+        })
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Score, E> {
+        Ok(Score {
+            amount: value.into(),
+            decimals: 0,
+        })
+    }
+
+    fn visit_i128<E: de::Error>(self, value: i128) -> Result<Score, E> {
+        Ok(Score {
+            amount: value,
+            decimals: 0,
+        })
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<Score, E> {
+        let amount = i128::try_from(value).map_err(|_| {
+            E::custom(ScoreError::Overflow {
+                input: value.to_string(),
+            })
+        })?;
+        Ok(Score {
+            amount,
+            decimals: 0,
+        })
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Score, E> {
+        if value.fract() != 0.0 || value.abs() > MAX_SAFE_INTEGER {
+            return Err(E::custom(ScoreError::InexactNumber(value)));
+        }
+        Ok(Score {
+            amount: value as i128,
+            decimals: 0,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Score, A::Error> {
+        let mut amount: Option<Score> = None;
+        let mut decimals: Option<u8> = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "amount" => amount = Some(map.next_value()?),
+                "decimals" => decimals = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+
+        let amount = amount.ok_or_else(|| de::Error::missing_field("amount"))?;
+        if amount.decimals != 0 {
+            return Err(de::Error::custom(format!(
+                "score amount {amount} must be an integer; use `decimals` to place the point"
+            )));
+        }
+        Score::new(amount.amount, decimals.unwrap_or(0)).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(input: &str) -> Score {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_and_formats_decimals() {
+        assert_eq!(score("12.50").to_string(), "12.5");
+        assert_eq!(score("-0.05").to_string(), "-0.05");
+        assert_eq!(score("+7").to_string(), "7");
+        assert_eq!(score(".5").to_string(), "0.5");
+        assert_eq!(Score::from_mist(1_500_000_000).to_string(), "1.5");
+        assert_eq!(Score::from_mist(1).to_string(), "0.000000001");
+        assert_eq!(score(&i128::MIN.to_string()).amount, i128::MIN);
+    }
+
+    #[test]
+    fn compares_by_value_across_scales() {
+        assert_eq!(score("1.5"), score("1.500"));
+        assert_eq!(Score::from_mist(2_000_000_000), score("2"));
+        assert!(score("-1.5") < score("-1.2"));
+        assert!(score("-0.5") < score("0.3"));
+        assert!(score("10") > score("9.999999999999999999"));
+        assert!(Score::new(i128::MAX, 18).unwrap() < Score::new(i128::MAX, 0).unwrap());
+    }
+
+    #[test]
+    fn rejects_malformed_scores() {
+        assert_eq!("".parse::<Score>(), Err(ScoreError::Empty));
+        assert_eq!("-".parse::<Score>(), Err(ScoreError::Empty));
+        assert!(matches!(
+            "1e9".parse::<Score>(),
+            Err(ScoreError::InvalidCharacter { character: 'e', .. })
+        ));
+        assert!(matches!(
+            "0.1234567890123456789".parse::<Score>(),
+            Err(ScoreError::TooManyDecimals { decimals: 19, .. })
+        ));
+        assert!(matches!(
+            "170141183460469231731687303715884105728".parse::<Score>(),
+            Err(ScoreError::Overflow { .. })
+        ));
+    }
+
+    #[test]
+    fn deserializes_every_wire_form() {
+        let parse = |json: &str| ron::from_str::<Score>(json);
+        assert_eq!(parse("\"-3.25\"").unwrap(), score("-3.25"));
+        assert_eq!(parse("42").unwrap(), score("42"));
+        assert_eq!(
+            parse("{\"amount\": \"1500000000\", \"decimals\": 9}").unwrap(),
+            Score::from_mist(1_500_000_000)
+        );
+        assert!(parse("1.5").is_err());
+        assert!(parse("{\"decimals\": 9}").is_err());
+        assert!(parse("{\"amount\": \"1\", \"decimals\": 30}").is_err());
+    }
+}
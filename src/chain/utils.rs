@@ -1,6 +1,3 @@
-use wasm_bindgen::prelude::*;
-
-
 // #[wasm_bindgen]
 // extern "C" {
 //     // Use `js_namespace` here to bind `console.log(..)` instead of just
@@ -0,0 +1,314 @@
+//! Scoring rules for the three victories, computed from raw on-chain activity.
+//!
+//! The web app only fetches records (balances, owned objects, transactions); every
+//! client turns them into the same leaderboards here.
+//!
+//! - Economic: profit and loss in SUI between the earliest and latest balance snapshot.
+//! - Cultural: `TYPE_POINTS` for each distinct Move type owned, ignoring type
+//!   arguments, plus the rarity points of every NFT owned.
+//! - Diplomatic: number of distinct addresses the player transacted with.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Deserializer};
+
+use super::{
+    ledger::{Player, Victory},
+    score::Score,
+};
+
+/// Cultural points for each distinct Move type a player owns.
+pub const TYPE_POINTS: u64 = 10;
+
+/// Everything the scoring rules look at for one address.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Activity {
+    pub address: String,
+    #[serde(default)]
+    pub balances: Vec<BalanceSnapshot>,
+    #[serde(default)]
+    pub objects: Vec<OwnedObject>,
+    #[serde(default)]
+    pub transactions: Vec<Transaction>,
+}
+
+/// SUI balance of the player at the end of an epoch.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BalanceSnapshot {
+    pub epoch: u64,
+    #[serde(deserialize_with = "mist")]
+    pub mist: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OwnedObject {
+    pub object_id: String,
+    /// Full Move type, e.g. `0x2::coin::Coin<0x2::sui::SUI>`.
+    pub type_name: String,
+    /// Set for NFTs only.
+    #[serde(default)]
+    pub rarity: Option<Rarity>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl Rarity {
+    pub fn points(self) -> u64 {
+        match self {
+            Rarity::Common => 1,
+            Rarity::Uncommon => 2,
+            Rarity::Rare => 5,
+            Rarity::Epic => 10,
+            Rarity::Legendary => 25,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Transaction {
+    pub digest: String,
+    pub sender: String,
+    #[serde(default)]
+    pub recipients: Vec<String>,
+}
+
+/// Accepts MIST as a decimal string, as the Sui SDK returns it, or as a safe integer.
+fn mist<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(u64),
+        Text(String),
+    }
+
+    match Raw::deserialize(deserializer)? {
+        Raw::Number(mist) => Ok(mist),
+        Raw::Text(text) => text.trim().parse().map_err(|_| {
+            serde::de::Error::custom(format!("balance {text:?} is not a whole MIST amount"))
+        }),
+    }
+}
+
+/// Canonical `0x` + 64 lowercase hex digits form, so `0x2` and `0x0…02` match.
+/// Anything that is not a hex address is only lowercased.
+pub fn normalize_address(address: &str) -> String {
+    let address = address.trim().to_ascii_lowercase();
+    let hex = address.strip_prefix("0x").unwrap_or(&address);
+    if hex.is_empty() || hex.len() > 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return address;
+    }
+    format!("0x{hex:0>64}")
+}
+
+/// Move type without its type arguments: `0x2::coin::Coin<T>` becomes `0x2::coin::Coin`.
+fn base_type(type_name: &str) -> &str {
+    type_name.split('<').next().unwrap_or(type_name).trim()
+}
+
+pub fn economic_score(activity: &Activity) -> Score {
+    let first = activity.balances.iter().min_by_key(|s| s.epoch);
+    let last = activity.balances.iter().max_by_key(|s| s.epoch);
+    let pnl = match (first, last) {
+        (Some(first), Some(last)) => i128::from(last.mist) - i128::from(first.mist),
+        _ => 0,
+    };
+    Score::from_mist(pnl)
+}
+
+pub fn cultural_score(activity: &Activity) -> Score {
+    let types: BTreeSet<&str> = activity
+        .objects
+        .iter()
+        .map(|object| base_type(&object.type_name))
+        .collect();
+
+    // An object listed twice still counts once.
+    let rarities: BTreeMap<&str, Rarity> = activity
+        .objects
+        .iter()
+        .filter_map(|object| Some((object.object_id.as_str(), object.rarity?)))
+        .collect();
+    let rarity_points: u64 = rarities.values().map(|rarity| rarity.points()).sum();
+
+    Score::from(types.len() as u64 * TYPE_POINTS + rarity_points)
+}
+
+pub fn diplomatic_score(activity: &Activity) -> Score {
+    let player = normalize_address(&activity.address);
+    let counterparties: BTreeSet<String> = activity
+        .transactions
+        .iter()
+        .flat_map(|tx| std::iter::once(&tx.sender).chain(&tx.recipients))
+        .map(|address| normalize_address(address))
+        .filter(|address| *address != player)
+        .collect();
+
+    Score::from(counterparties.len() as u64)
+}
+
+pub fn score(victory: Victory, activity: &Activity) -> Score {
+    match victory {
+        Victory::Economic => economic_score(activity),
+        Victory::Cultural => cultural_score(activity),
+        Victory::Diplomatic => diplomatic_score(activity),
+    }
+}
+
+/// One leaderboard entry per address, in address order. Records sent for the same
+/// address under different spellings are merged before scoring, and a transaction
+/// reported twice is only kept once.
+pub fn leaderboard(victory: Victory, activities: &[Activity]) -> Vec<Player> {
+    let mut merged: BTreeMap<String, Activity> = BTreeMap::new();
+    let mut digests: BTreeSet<(String, &str)> = BTreeSet::new();
+    for activity in activities {
+        let address = normalize_address(&activity.address);
+        let entry = merged.entry(address.clone()).or_insert_with(|| Activity {
+            address,
+            ..Default::default()
+        });
+        entry.balances.extend(activity.balances.iter().cloned());
+        entry.objects.extend(activity.objects.iter().cloned());
+        entry.transactions.extend(
+            activity
+                .transactions
+                .iter()
+                .filter(|tx| digests.insert((entry.address.clone(), tx.digest.as_str())))
+                .cloned(),
+        );
+    }
+
+    merged
+        .into_values()
+        .map(|activity| Player {
+            score: score(victory, &activity),
+            address: activity.address,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "0xa11ce";
+    const BOB: &str = "0xb0b";
+    const CAROL: &str = "0xca201";
+
+    fn snapshot(epoch: u64, mist: u64) -> BalanceSnapshot {
+        BalanceSnapshot { epoch, mist }
+    }
+
+    fn object(id: &str, type_name: &str, rarity: Option<Rarity>) -> OwnedObject {
+        OwnedObject {
+            object_id: id.to_string(),
+            type_name: type_name.to_string(),
+            rarity,
+        }
+    }
+
+    fn transaction(sender: &str, recipients: &[&str]) -> Transaction {
+        Transaction {
+            digest: format!("{sender}->{recipients:?}"),
+            sender: sender.to_string(),
+            recipients: recipients.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+
+    fn activity(address: &str) -> Activity {
+        Activity {
+            address: address.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn economic_is_pnl_between_first_and_last_epoch() {
+        let mut alice = activity(ALICE);
+        // Out of order on purpose; the epoch decides.
+        alice.balances = vec![
+            snapshot(12, 3_250_000_000),
+            snapshot(10, 1_000_000_000),
+            snapshot(11, 9_000_000_000),
+        ];
+        assert_eq!(economic_score(&alice).to_string(), "2.25");
+
+        alice.balances = vec![snapshot(1, 5_000_000_000), snapshot(2, 4_500_000_000)];
+        assert_eq!(economic_score(&alice).to_string(), "-0.5");
+
+        alice.balances = vec![snapshot(1, 5_000_000_000)];
+        assert_eq!(economic_score(&alice), Score::default());
+    }
+
+    #[test]
+    fn cultural_counts_distinct_types_and_rarity() {
+        let mut alice = activity(ALICE);
+        alice.objects = vec![
+            object("0x1", "0x2::coin::Coin<0x2::sui::SUI>", None),
+            object("0x2", "0x2::coin::Coin<0xabc::usdc::USDC>", None),
+            object("0x3", "0xabc::art::Painting", Some(Rarity::Legendary)),
+            object("0x3", "0xabc::art::Painting", Some(Rarity::Legendary)),
+            object("0x4", "0xabc::art::Painting", Some(Rarity::Common)),
+        ];
+        // Coin and Painting, plus 25 + 1 rarity points.
+        assert_eq!(cultural_score(&alice), Score::from(2 * TYPE_POINTS + 26));
+    }
+
+    #[test]
+    fn diplomatic_counts_unique_counterparties() {
+        let mut alice = activity(ALICE);
+        alice.transactions = vec![
+            transaction(ALICE, &[BOB]),
+            transaction(BOB, &[ALICE]),
+            transaction(
+                ALICE,
+                &[
+                    CAROL,
+                    "0x00000000000000000000000000000000000000000000000000000000000b0b",
+                ],
+            ),
+            transaction("0xA11CE", &[]),
+        ];
+        assert_eq!(diplomatic_score(&alice), Score::from(2));
+    }
+
+    #[test]
+    fn leaderboard_merges_address_spellings() {
+        let mut first = activity(BOB);
+        first.transactions = vec![transaction(BOB, &[ALICE])];
+        let mut second = activity("0x0B0B");
+        second.transactions = vec![transaction(BOB, &[CAROL]), transaction(BOB, &[ALICE])];
+
+        let players = leaderboard(Victory::Diplomatic, &[first, second, activity(ALICE)]);
+        assert_eq!(players.len(), 2);
+        let bob = players
+            .iter()
+            .find(|p| p.address == normalize_address(BOB))
+            .unwrap();
+        assert_eq!(bob.score, Score::from(2));
+    }
+
+    #[test]
+    fn deserializes_sdk_shaped_records() {
+        let alice: Activity = ron::from_str(
+            r#"(
+                address: "0xa11ce",
+                balances: [(epoch: 3, mist: "18446744073709551615"), (epoch: 1, mist: 0)],
+                objects: [(object_id: "0x9", type_name: "0x2::kiosk::Kiosk", rarity: Some(epic))],
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(economic_score(&alice).to_string(), "18446744073.709551615");
+        assert_eq!(cultural_score(&alice), Score::from(TYPE_POINTS + 10));
+        assert_eq!(diplomatic_score(&alice), Score::default());
+
+        assert!(ron::from_str::<BalanceSnapshot>(r#"(epoch: 1, mist: "1.5")"#).is_err());
+    }
+}
@@ -0,0 +1,342 @@
+//! Input action map: gameplay reads `Actions` rather than keys, so every action can be
+//! rebound in `Bindings` and driven from a keyboard or a gamepad.
+
+use std::{collections::HashMap, fmt};
+
+use bevy::{input::InputSystem, prelude::*};
+
+use crate::{
+    ui::{ResetMapEvent, ShowLedgerEvent},
+    AppState,
+};
+
+/// Analog inputs count as pressed from this value on.
+const PRESS_THRESHOLD: f32 = 0.5;
+
+/// Stick values below this are treated as the stick resting.
+const STICK_DEADZONE: f32 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    ZoomIn,
+    ZoomOut,
+    FollowPlayer,
+    OpenLedger,
+    Reset,
+}
+
+impl Action {
+    pub const ALL: [Action; 13] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::PanUp,
+        Action::PanDown,
+        Action::PanLeft,
+        Action::PanRight,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::FollowPlayer,
+        Action::OpenLedger,
+        Action::Reset,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::PanUp => "Pan camera up",
+            Action::PanDown => "Pan camera down",
+            Action::PanLeft => "Pan camera left",
+            Action::PanRight => "Pan camera right",
+            Action::ZoomIn => "Zoom in",
+            Action::ZoomOut => "Zoom out",
+            Action::FollowPlayer => "Follow player",
+            Action::OpenLedger => "Open ledger",
+            Action::Reset => "Reset map",
+        }
+    }
+}
+
+/// A gamepad button, or one direction of a stick axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GamepadInput {
+    Button(GamepadButtonType),
+    Axis {
+        axis: GamepadAxisType,
+        positive: bool,
+    },
+}
+
+impl fmt::Display for GamepadInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GamepadInput::Button(button) => write!(f, "{button:?}"),
+            GamepadInput::Axis { axis, positive } => {
+                write!(f, "{axis:?} {}", if *positive { '+' } else { '-' })
+            }
+        }
+    }
+}
+
+/// Name of a key as shown in the settings, e.g. `W` rather than `KeyW`.
+pub fn key_label(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+    ["Key", "Digit"]
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .map_or(name.clone(), str::to_string)
+}
+
+/// The key and gamepad input bound to every action; changed in the settings panel.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct Bindings {
+    pub keys: HashMap<Action, KeyCode>,
+    pub gamepad: HashMap<Action, GamepadInput>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use GamepadAxisType::*;
+        use GamepadButtonType::*;
+
+        let axis = |axis, positive| GamepadInput::Axis { axis, positive };
+        let keys = [
+            (Action::MoveUp, KeyCode::ArrowUp),
+            (Action::MoveDown, KeyCode::ArrowDown),
+            (Action::MoveLeft, KeyCode::ArrowLeft),
+            (Action::MoveRight, KeyCode::ArrowRight),
+            (Action::PanUp, KeyCode::KeyW),
+            (Action::PanDown, KeyCode::KeyS),
+            (Action::PanLeft, KeyCode::KeyA),
+            (Action::PanRight, KeyCode::KeyD),
+            (Action::ZoomIn, KeyCode::KeyX),
+            (Action::ZoomOut, KeyCode::KeyZ),
+            (Action::FollowPlayer, KeyCode::KeyF),
+            // Not a letter, so typing in the ledger filter does not close it.
+            (Action::OpenLedger, KeyCode::Tab),
+            (Action::Reset, KeyCode::KeyR),
+        ];
+        let gamepad = [
+            (Action::MoveUp, axis(LeftStickY, true)),
+            (Action::MoveDown, axis(LeftStickY, false)),
+            (Action::MoveLeft, axis(LeftStickX, false)),
+            (Action::MoveRight, axis(LeftStickX, true)),
+            (Action::PanUp, axis(RightStickY, true)),
+            (Action::PanDown, axis(RightStickY, false)),
+            (Action::PanLeft, axis(RightStickX, false)),
+            (Action::PanRight, axis(RightStickX, true)),
+            (Action::ZoomIn, GamepadInput::Button(RightTrigger2)),
+            (Action::ZoomOut, GamepadInput::Button(LeftTrigger2)),
+            (Action::FollowPlayer, GamepadInput::Button(RightThumb)),
+            (Action::OpenLedger, GamepadInput::Button(Start)),
+            (Action::Reset, GamepadInput::Button(Select)),
+        ];
+
+        Bindings {
+            keys: keys.into_iter().collect(),
+            gamepad: gamepad.into_iter().collect(),
+        }
+    }
+}
+
+impl Bindings {
+    /// Binds `key` to `action`; an action it was bound to gets `action`'s old key.
+    pub fn bind_key(&mut self, action: Action, key: KeyCode) {
+        rebind(&mut self.keys, action, key);
+    }
+
+    pub fn bind_gamepad(&mut self, action: Action, input: GamepadInput) {
+        rebind(&mut self.gamepad, action, input);
+    }
+}
+
+/// Every input drives at most one action, so binding one swaps it with its old action.
+fn rebind<T: Copy + PartialEq>(map: &mut HashMap<Action, T>, action: Action, input: T) {
+    let previous = map.insert(action, input);
+    let taken_from = map
+        .iter()
+        .find(|(other, bound)| **other != action && **bound == input)
+        .map(|(other, _)| *other);
+    if let Some(other) = taken_from {
+        match previous {
+            Some(previous) => map.insert(other, previous),
+            None => map.remove(&other),
+        };
+    }
+}
+
+/// How strongly every action is held this frame, from 0 to 1.
+#[derive(Resource, Default)]
+pub struct Actions {
+    values: HashMap<Action, f32>,
+    previous: HashMap<Action, f32>,
+    /// Cleared while the settings panel waits for a new binding, so the key pressed
+    /// for it does not also act. Inputs are still tracked meanwhile, so a key held
+    /// when it is set again does not count as just pressed.
+    pub enabled: bool,
+}
+
+impl Actions {
+    pub fn value(&self, action: Action) -> f32 {
+        if !self.enabled {
+            return 0.0;
+        }
+        self.values.get(&action).copied().unwrap_or_default()
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.value(action) >= PRESS_THRESHOLD
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.pressed(action)
+            && self.previous.get(&action).copied().unwrap_or_default() < PRESS_THRESHOLD
+    }
+
+    /// `positive` minus `negative`, e.g. for one axis of a movement direction.
+    pub fn axis(&self, negative: Action, positive: Action) -> f32 {
+        self.value(positive) - self.value(negative)
+    }
+}
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Bindings>()
+            .insert_resource(Actions {
+                enabled: true,
+                ..default()
+            })
+            .add_systems(PreUpdate, update_actions.after(InputSystem))
+            .add_systems(Update, menu_actions);
+    }
+}
+
+fn update_actions(
+    bindings: Res<Bindings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    button_axes: Res<Axis<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut actions: ResMut<Actions>,
+) {
+    let actions = &mut *actions;
+    actions.previous = std::mem::take(&mut actions.values);
+
+    for action in Action::ALL {
+        let mut value = match bindings.keys.get(&action) {
+            Some(key) if keys.pressed(*key) => 1.0,
+            _ => 0.0,
+        };
+        if let Some(input) = bindings.gamepad.get(&action) {
+            for gamepad in gamepads.iter() {
+                value = f32::max(
+                    value,
+                    gamepad_value(gamepad, *input, &gamepad_buttons, &button_axes, &axes),
+                );
+            }
+        }
+        if value > 0.0 {
+            actions.values.insert(action, value);
+        }
+    }
+}
+
+fn gamepad_value(
+    gamepad: Gamepad,
+    input: GamepadInput,
+    buttons: &ButtonInput<GamepadButton>,
+    button_axes: &Axis<GamepadButton>,
+    axes: &Axis<GamepadAxis>,
+) -> f32 {
+    match input {
+        GamepadInput::Button(button_type) => {
+            let button = GamepadButton::new(gamepad, button_type);
+            // Triggers report how far they are pulled; other buttons are on or off.
+            let pressed = if buttons.pressed(button) { 1.0 } else { 0.0 };
+            button_axes.get(button).unwrap_or(pressed).max(pressed)
+        }
+        GamepadInput::Axis { axis, positive } => {
+            let value = axes
+                .get(GamepadAxis::new(gamepad, axis))
+                .unwrap_or_default();
+            let value = if positive { value } else { -value };
+            if value < STICK_DEADZONE {
+                0.0
+            } else {
+                value.min(1.0)
+            }
+        }
+    }
+}
+
+/// Actions standing in for the buttons of the top right menu.
+fn menu_actions(
+    actions: Res<Actions>,
+    state: Res<State<AppState>>,
+    mut reset_events: EventWriter<ResetMapEvent>,
+    mut ledger_events: EventWriter<ShowLedgerEvent>,
+) {
+    match state.get() {
+        AppState::Finished => {
+            if actions.just_pressed(Action::Reset) {
+                reset_events.send(ResetMapEvent);
+            }
+            if actions.just_pressed(Action::OpenLedger) {
+                ledger_events.send(ShowLedgerEvent(true));
+            }
+        }
+        AppState::Ledger if actions.just_pressed(Action::OpenLedger) => {
+            ledger_events.send(ShowLedgerEvent(false));
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_a_used_key_swaps_it() {
+        let mut bindings = Bindings::default();
+        bindings.bind_key(Action::Reset, KeyCode::KeyF);
+        assert_eq!(bindings.keys[&Action::Reset], KeyCode::KeyF);
+        assert_eq!(bindings.keys[&Action::FollowPlayer], KeyCode::KeyR);
+
+        bindings.bind_key(Action::Reset, KeyCode::KeyQ);
+        assert_eq!(bindings.keys[&Action::Reset], KeyCode::KeyQ);
+        assert_eq!(bindings.keys[&Action::FollowPlayer], KeyCode::KeyR);
+    }
+
+    #[test]
+    fn binding_a_used_input_to_an_unbound_action_unbinds_the_other() {
+        let mut bindings = Bindings::default();
+        bindings.gamepad.remove(&Action::Reset);
+        let start = GamepadInput::Button(GamepadButtonType::Start);
+        bindings.bind_gamepad(Action::Reset, start);
+        assert_eq!(bindings.gamepad[&Action::Reset], start);
+        assert!(!bindings.gamepad.contains_key(&Action::OpenLedger));
+    }
+
+    #[test]
+    fn key_labels_drop_prefixes() {
+        assert_eq!(key_label(KeyCode::KeyW), "W");
+        assert_eq!(key_label(KeyCode::Digit1), "1");
+        assert_eq!(key_label(KeyCode::ArrowUp), "ArrowUp");
+    }
+}
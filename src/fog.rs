@@ -0,0 +1,217 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    utils::HashSet,
+};
+
+use crate::{
+    chain::{
+        bridge::{self, ExploredReceived},
+        ledger::LocalPlayer,
+    },
+    map::{
+        chunk::TileChunk,
+        explored::{in_sight, Explored},
+        CHUNK_SIZE, TILE_SIZE,
+    },
+    Game, Seed,
+};
+
+/// Tiles the player sees around them, in every direction.
+pub const SIGHT_RADIUS: i32 = 7;
+
+/// Drawn over structures and other units so they are hidden too, but under the player.
+const FOG_Z: f32 = 0.97;
+
+const HIDDEN_ALPHA: u8 = 255;
+/// Explored tiles out of sight are dimmed rather than hidden.
+const EXPLORED_ALPHA: u8 = 140;
+
+pub struct FogPlugin;
+
+impl Plugin for FogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Fog>()
+            // After the player has moved and new chunks have been spawned this frame.
+            .add_systems(
+                PostUpdate,
+                (
+                    receive_explored,
+                    reveal_around_player,
+                    add_overlays,
+                    paint_fog,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// The local player's explored tiles and what is in sight right now.
+#[derive(Resource, Default)]
+pub struct Fog {
+    explored: Explored,
+    /// Tile the player saw from last, `None` before they are placed on the map.
+    centre: Option<IVec2>,
+    /// Chunks whose overlay is out of date.
+    dirty: HashSet<IVec2>,
+    repaint_all: bool,
+}
+
+impl Fog {
+    pub fn is_explored(&self, cell: IVec2) -> bool {
+        self.explored.is_explored(cell.into())
+    }
+
+    pub fn in_sight(&self, cell: IVec2) -> bool {
+        self.centre
+            .is_some_and(|centre| in_sight(centre.into(), SIGHT_RADIUS, cell.into()))
+    }
+
+    /// Overlay alpha of `cell`.
+    fn alpha(&self, cell: IVec2) -> u8 {
+        if self.in_sight(cell) {
+            0
+        } else if self.is_explored(cell) {
+            EXPLORED_ALPHA
+        } else {
+            HIDDEN_ALPHA
+        }
+    }
+
+    /// Marks the chunks around `centre` the sight circle reaches into.
+    fn touch(&mut self, centre: IVec2) {
+        let size = CHUNK_SIZE as i32;
+        let min = (centre - SIGHT_RADIUS).div_euclid(IVec2::splat(size));
+        let max = (centre + SIGHT_RADIUS).div_euclid(IVec2::splat(size));
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.dirty.insert(IVec2::new(x, y));
+            }
+        }
+    }
+}
+
+/// Fog over one chunk: a `CHUNK_SIZE` x `CHUNK_SIZE` image with a pixel per tile.
+#[derive(Component)]
+struct FogOverlay {
+    coord: IVec2,
+}
+
+fn receive_explored(
+    mut received: EventReader<ExploredReceived>,
+    seed: Option<Res<Seed>>,
+    local_player: Res<LocalPlayer>,
+    mut fog: ResMut<Fog>,
+) {
+    // Exploration is per map and player. Resetting the map keeps the seed, and with
+    // it what was explored; tiles imported along with a new seed are applied below.
+    if seed.is_some_and(|seed| seed.is_changed()) || local_player.is_changed() {
+        fog.explored = Explored::default();
+        fog.centre = None;
+        fog.repaint_all = true;
+    }
+    // Imported tiles add to what was explored since the map loaded.
+    if let Some(ExploredReceived(explored)) = received.read().last() {
+        fog.explored.extend(explored);
+        fog.centre = None;
+        fog.repaint_all = true;
+    }
+    if fog.repaint_all {
+        bridge::publish_explored(&fog.explored);
+    }
+}
+
+fn reveal_around_player(game: Res<Game>, mut fog: ResMut<Fog>) {
+    // Nothing is in sight until `roster` has put the player on their spawn tile.
+    let centre = game.player.spawn.map(|_| game.player.cell);
+    if centre == fog.centre {
+        return;
+    }
+
+    if let Some(previous) = fog.centre {
+        fog.touch(previous);
+    }
+    fog.centre = centre;
+    let Some(centre) = centre else {
+        return;
+    };
+    fog.touch(centre);
+    if fog.explored.reveal(centre.into(), SIGHT_RADIUS) {
+        bridge::publish_explored(&fog.explored);
+    }
+}
+
+/// Gives every newly streamed chunk its fog overlay, painted for the current state.
+fn add_overlays(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    fog: Res<Fog>,
+    chunks: Query<(Entity, &TileChunk), Added<TileChunk>>,
+) {
+    for (entity, chunk) in &chunks {
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: CHUNK_SIZE as u32,
+                height: CHUNK_SIZE as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, HIDDEN_ALPHA],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        paint(&mut image, chunk.coord, &fog);
+
+        // Chunk children are positioned relative to the centre of its first tile.
+        let centre = Vec2::splat((CHUNK_SIZE as f32 - 1.0) / 2.0 * TILE_SIZE);
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::splat(CHUNK_SIZE as f32 * TILE_SIZE)),
+                        ..default()
+                    },
+                    texture: images.add(image),
+                    transform: Transform::from_translation(centre.extend(FOG_Z)),
+                    ..default()
+                },
+                FogOverlay { coord: chunk.coord },
+            ));
+        });
+    }
+}
+
+fn paint_fog(
+    mut images: ResMut<Assets<Image>>,
+    mut fog: ResMut<Fog>,
+    overlays: Query<(&FogOverlay, &Handle<Image>)>,
+) {
+    if !fog.repaint_all && fog.dirty.is_empty() {
+        return;
+    }
+    for (overlay, handle) in &overlays {
+        if !fog.repaint_all && !fog.dirty.contains(&overlay.coord) {
+            continue;
+        }
+        if let Some(image) = images.get_mut(handle) {
+            paint(image, overlay.coord, &fog);
+        }
+    }
+    fog.dirty.clear();
+    fog.repaint_all = false;
+}
+
+fn paint(image: &mut Image, coord: IVec2, fog: &Fog) {
+    let size = CHUNK_SIZE as i32;
+    let first = coord * size;
+    for y in 0..size {
+        for x in 0..size {
+            // Image rows run top to bottom, tile rows bottom to top.
+            let pixel = ((size - 1 - y) * size + x) as usize * 4;
+            image.data[pixel + 3] = fog.alpha(first + IVec2::new(x, y));
+        }
+    }
+}
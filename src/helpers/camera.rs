@@ -1,12 +1,104 @@
-use crate::AppState;
-use bevy::{input::ButtonInput, math::Vec3, prelude::*, render::camera::Camera};
+use crate::{
+    controls::{Action, Actions},
+    map::{cell_corner, Terrain},
+    AppState, Game,
+};
+use bevy::{
+    input::{
+        mouse::{MouseScrollUnit, MouseWheel},
+        ButtonInput,
+    },
+    prelude::*,
+    render::camera::Camera,
+    window::PrimaryWindow,
+};
+
+/// Zoom limits; larger scales show more of the map.
+const MIN_SCALE: f32 = 0.5;
+const MAX_SCALE: f32 = 3.0;
+
+/// Panning speed of the pan actions in screen pixels per second, so it feels the same
+/// at any zoom.
+const PAN_SPEED: f32 = 500.0;
+
+/// Zoom factor per mouse wheel line, and per second while a zoom action is held.
+const WHEEL_ZOOM: f32 = 1.15;
+const KEY_ZOOM: f32 = 2.5;
+
+/// Screen pixels panned per mouse wheel line with Shift held.
+const WHEEL_PAN: f32 = 40.0;
+
+/// Pixel scroll deltas (touchpads, browsers) per wheel line.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// How quickly the zoom and the followed position catch up, per second. Smoothing is
+/// exponential, so it looks the same at any frame rate.
+const ZOOM_SMOOTHING: f32 = 12.0;
+const FOLLOW_SMOOTHING: f32 = 6.0;
+
+/// Following jumps rather than glides when the player is further than this from the
+/// camera, e.g. when they are first placed on the map.
+const FOLLOW_SNAP_DISTANCE: f32 = 2048.0;
 
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::Setup), setup_camera);
-        app.add_systems(Update, movement.run_if(in_state(AppState::Finished)));
+        app.init_resource::<CameraController>()
+            .add_systems(OnEnter(AppState::Setup), setup_camera)
+            .add_systems(OnEnter(AppState::Build), follow_again)
+            .add_systems(
+                Update,
+                (
+                    action_controls,
+                    wheel_controls,
+                    drag_pan,
+                    smooth_zoom,
+                    follow_player,
+                    clamp_to_map,
+                )
+                    .chain()
+                    .in_set(CameraControl)
+                    .run_if(in_state(AppState::Finished)),
+            );
+    }
+}
+
+/// Systems moving the camera; others that move it directly run before them so the
+/// result is still clamped to the map.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CameraControl;
+
+#[derive(Resource)]
+pub struct CameraController {
+    /// Keep the player in the middle of the view; panning turns this off and the
+    /// follow action back on.
+    pub follow: bool,
+    /// Scale the projection is easing towards.
+    target_scale: f32,
+    /// Screen position the zoom is centred on, `None` for the middle of the view.
+    zoom_anchor: Option<Vec2>,
+    /// Cursor position during the last frame of a drag.
+    drag_from: Option<Vec2>,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        CameraController {
+            follow: true,
+            target_scale: 1.0,
+            zoom_anchor: None,
+            drag_from: None,
+        }
+    }
+}
+
+impl CameraController {
+    /// Eases the zoom by `factor`, above one to zoom out, keeping `anchor` (a screen
+    /// position) in place.
+    pub fn zoom_by(&mut self, factor: f32, anchor: Option<Vec2>) {
+        self.target_scale *= factor;
+        self.zoom_anchor = anchor;
     }
 }
 
@@ -14,45 +106,212 @@ fn setup_camera(mut commands: Commands) {
     commands.spawn(Camera2dBundle::default());
 }
 
-#[allow(dead_code)]
-pub fn movement(
+fn follow_again(mut controller: ResMut<CameraController>) {
+    controller.follow = true;
+}
+
+/// Pan, zoom and follow actions, by default WASD, Z and X, and F.
+fn action_controls(
     time: Res<Time>,
+    actions: Res<Actions>,
+    mut controller: ResMut<CameraController>,
+    mut cameras: Query<(&mut Transform, &OrthographicProjection), With<Camera>>,
+) {
+    let direction = Vec2::new(
+        actions.axis(Action::PanLeft, Action::PanRight),
+        actions.axis(Action::PanDown, Action::PanUp),
+    )
+    // Sticks held diagonally must not pan faster.
+    .clamp_length_max(1.0);
+
+    let zoom = actions.axis(Action::ZoomIn, Action::ZoomOut);
+    if zoom != 0.0 {
+        controller.zoom_by(KEY_ZOOM.powf(zoom * time.delta_seconds()), None);
+    }
+
+    if actions.just_pressed(Action::FollowPlayer) {
+        controller.follow = true;
+    }
+    if direction == Vec2::ZERO {
+        return;
+    }
+    controller.follow = false;
+    for (mut transform, projection) in &mut cameras {
+        let step = direction * PAN_SPEED * projection.scale * time.delta_seconds();
+        transform.translation += step.extend(0.0);
+    }
+}
+
+/// The wheel zooms towards the cursor; with Shift held, or sideways, it pans.
+fn wheel_controls(
+    mut wheel: EventReader<MouseWheel>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    interactions: Query<&Interaction>,
+    mut controller: ResMut<CameraController>,
+    mut cameras: Query<(&mut Transform, &OrthographicProjection), With<Camera>>,
 ) {
-    for (mut transform, mut ortho) in query.iter_mut() {
-        let mut direction = Vec3::ZERO;
+    let mut lines = Vec2::ZERO;
+    for event in wheel.read() {
+        let delta = Vec2::new(event.x, event.y);
+        lines += match event.unit {
+            MouseScrollUnit::Line => delta,
+            MouseScrollUnit::Pixel => delta / PIXELS_PER_LINE,
+        };
+    }
+    // Scrolling over the UI is meant for the UI.
+    if lines == Vec2::ZERO || interactions.iter().any(|i| *i != Interaction::None) {
+        return;
+    }
 
-        if keyboard_input.pressed(KeyCode::KeyA) {
-            direction -= Vec3::new(1.0, 0.0, 0.0);
-        }
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let (zoom, pan) = if shift {
+        (0.0, Vec2::new(lines.x + lines.y, 0.0))
+    } else {
+        (lines.y, Vec2::new(lines.x, 0.0))
+    };
 
-        if keyboard_input.pressed(KeyCode::KeyD) {
-            direction += Vec3::new(1.0, 0.0, 0.0);
+    if pan != Vec2::ZERO {
+        controller.follow = false;
+        for (mut transform, projection) in &mut cameras {
+            transform.translation -= (pan * WHEEL_PAN * projection.scale).extend(0.0);
         }
+    }
+    if zoom != 0.0 {
+        let cursor = windows.get_single().ok().and_then(Window::cursor_position);
+        controller.zoom_by(WHEEL_ZOOM.powf(-zoom), cursor);
+    }
+}
 
-        if keyboard_input.pressed(KeyCode::KeyW) {
-            direction += Vec3::new(0.0, 1.0, 0.0);
-        }
+/// Dragging with the middle or right mouse button pans the map.
+fn drag_pan(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    interactions: Query<&Interaction>,
+    mut controller: ResMut<CameraController>,
+    mut cameras: Query<(&mut Transform, &OrthographicProjection), With<Camera>>,
+) {
+    let buttons = [MouseButton::Middle, MouseButton::Right];
+    let cursor = windows.get_single().ok().and_then(Window::cursor_position);
+    if mouse_buttons.any_just_pressed(buttons) {
+        let over_ui = interactions.iter().any(|i| *i != Interaction::None);
+        controller.drag_from = if over_ui { None } else { cursor };
+    }
+    if !mouse_buttons.any_pressed(buttons) {
+        controller.drag_from = None;
+    }
+    let (Some(from), Some(to)) = (controller.drag_from, cursor) else {
+        return;
+    };
+    controller.drag_from = Some(to);
+    if from == to {
+        return;
+    }
 
-        if keyboard_input.pressed(KeyCode::KeyS) {
-            direction -= Vec3::new(0.0, 1.0, 0.0);
-        }
+    controller.follow = false;
+    // Screen y grows downwards, world y upwards.
+    let delta = Vec2::new(to.x - from.x, from.y - to.y);
+    for (mut transform, projection) in &mut cameras {
+        transform.translation -= (delta * projection.scale).extend(0.0);
+    }
+}
 
-        if keyboard_input.pressed(KeyCode::KeyZ) {
-            ortho.scale += 0.1;
-        }
+/// Eases the projection scale towards the target, keeping the world point under the
+/// zoom anchor in place.
+fn smooth_zoom(
+    time: Res<Time>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut controller: ResMut<CameraController>,
+    mut cameras: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
+) {
+    controller.target_scale = controller.target_scale.clamp(MIN_SCALE, MAX_SCALE);
+    let target = controller.target_scale;
 
-        if keyboard_input.pressed(KeyCode::KeyX) {
-            ortho.scale -= 0.1;
+    for (mut transform, mut projection) in &mut cameras {
+        if projection.scale == target {
+            continue;
+        }
+        let blend = (-ZOOM_SMOOTHING * time.delta_seconds()).exp();
+        let mut scale = target + (projection.scale - target) * blend;
+        if (scale - target).abs() < 1e-3 {
+            scale = target;
         }
 
-        if ortho.scale < 0.5 {
-            ortho.scale = 0.5;
+        // While following, the player stays in the middle instead.
+        if let (Some(anchor), false, Ok(window)) = (
+            controller.zoom_anchor,
+            controller.follow,
+            windows.get_single(),
+        ) {
+            let from_centre = anchor - Vec2::new(window.width(), window.height()) / 2.0;
+            let from_centre = Vec2::new(from_centre.x, -from_centre.y);
+            transform.translation += (from_centre * (projection.scale - scale)).extend(0.0);
         }
+        projection.scale = scale;
+    }
+}
+
+fn follow_player(
+    time: Res<Time>,
+    game: Res<Game>,
+    controller: Res<CameraController>,
+    transforms: Query<&Transform, Without<Camera>>,
+    mut cameras: Query<&mut Transform, With<Camera>>,
+) {
+    if !controller.follow || game.player.spawn.is_none() {
+        return;
+    }
+    let Some(player) = game.player.entity.and_then(|e| transforms.get(e).ok()) else {
+        return;
+    };
+    let target = player.translation.truncate();
 
-        let z = transform.translation.z;
-        transform.translation += time.delta_seconds() * direction * 500.;
-        transform.translation.z = z;
+    let blend = (-FOLLOW_SMOOTHING * time.delta_seconds()).exp();
+    for mut transform in &mut cameras {
+        let position = transform.translation.truncate();
+        let next = if position.distance(target) > FOLLOW_SNAP_DISTANCE {
+            target
+        } else {
+            target + (position - target) * blend
+        };
+        transform.translation = next.extend(transform.translation.z);
+    }
+}
+
+/// Keeps the view inside the world when the palette gives it an edge, or centred on it
+/// when zoomed out further.
+fn clamp_to_map(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    terrain: Terrain,
+    mut cameras: Query<(&mut Transform, &OrthographicProjection), With<Camera>>,
+) {
+    let (Ok(window), Some(radius)) = (windows.get_single(), terrain.world_radius()) else {
+        return;
+    };
+    let world_min = cell_corner(IVec2::splat(-radius));
+    let world_max = cell_corner(IVec2::splat(radius));
+
+    for (mut transform, projection) in &mut cameras {
+        let half_view = Vec2::new(window.width(), window.height()) * projection.scale / 2.0;
+        let min = world_min + half_view;
+        let max = world_max - half_view;
+        let centre = (world_min + world_max) / 2.0;
+
+        let position = transform.translation.truncate();
+        let clamped = Vec2::new(
+            if min.x <= max.x {
+                position.x.clamp(min.x, max.x)
+            } else {
+                centre.x
+            },
+            if min.y <= max.y {
+                position.y.clamp(min.y, max.y)
+            } else {
+                centre.y
+            },
+        );
+        if clamped != position {
+            transform.translation = clamped.extend(transform.translation.z);
+        }
     }
 }
@@ -0,0 +1,236 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{prelude::*, ui::UiSystem};
+
+use super::camera::CameraController;
+use crate::AppState;
+
+/// Touches moving further than this many pixels pan instead of tapping.
+const TAP_SLOP: f32 = 12.0;
+
+/// Touches held longer than this are not taps.
+const TAP_SECONDS: f32 = 0.35;
+
+const DPAD_BUTTON: f32 = 56.0;
+const DPAD_RELEASED: Color = Color::rgba(0.15, 0.15, 0.15, 0.6);
+const DPAD_PRESSED: Color = Color::rgba(0.35, 0.35, 0.35, 0.8);
+
+pub struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tap>()
+            .init_resource::<VirtualDPad>()
+            .init_resource::<Gesture>()
+            .add_systems(Startup, spawn_dpad)
+            // Once the UI knows which touches are its own, before anything reads the
+            // tap as a click.
+            .add_systems(PreUpdate, touch_gestures.after(UiSystem::Focus))
+            .add_systems(
+                Update,
+                (show_dpad, read_dpad).before(crate::player_movement),
+            );
+    }
+}
+
+/// Screen position of a tap on the map this frame, handled like a left click there.
+#[derive(Resource, Default)]
+pub struct Tap {
+    position: Option<Vec2>,
+}
+
+impl Tap {
+    pub fn position(&self) -> Option<Vec2> {
+        self.position
+    }
+
+    /// Keeps systems running later this frame from handling the tap again.
+    pub fn consume(&mut self) {
+        self.position = None;
+    }
+}
+
+/// Direction held on the on-screen d-pad, zero when released.
+#[derive(Resource, Default)]
+pub struct VirtualDPad(pub IVec2);
+
+/// Touches on the map since the first of them went down.
+#[derive(Resource, Default)]
+struct Gesture {
+    started: HashMap<u64, f32>,
+    /// Touches that began on the UI; the UI handles those.
+    ignored: HashSet<u64>,
+    /// Set once the gesture moved or used two fingers, so lifting them is not a tap.
+    moved: bool,
+}
+
+#[derive(Component)]
+struct DPad;
+
+#[derive(Component)]
+struct DPadButton(IVec2);
+
+/// One finger pans, two pinch to zoom and pan, and a short touch that stays in place
+/// is a tap.
+#[allow(clippy::too_many_arguments)]
+fn touch_gestures(
+    time: Res<Time>,
+    touches: Res<Touches>,
+    interactions: Query<&Interaction>,
+    state: Res<State<AppState>>,
+    mut gesture: ResMut<Gesture>,
+    mut tap: ResMut<Tap>,
+    mut controller: ResMut<CameraController>,
+    mut cameras: Query<(&mut Transform, &OrthographicProjection), With<Camera>>,
+) {
+    tap.position = None;
+    let now = time.elapsed_seconds();
+
+    let over_ui = interactions.iter().any(|i| *i != Interaction::None);
+    for touch in touches.iter_just_pressed() {
+        gesture.started.insert(touch.id(), now);
+        if over_ui {
+            gesture.ignored.insert(touch.id());
+        }
+    }
+
+    let active: Vec<_> = touches
+        .iter()
+        .filter(|touch| !gesture.ignored.contains(&touch.id()))
+        .collect();
+    let on_map = *state.get() == AppState::Finished;
+
+    let mut pan = Vec2::ZERO;
+    match active.as_slice() {
+        [touch] => {
+            if touch.distance().length() > TAP_SLOP {
+                gesture.moved = true;
+            }
+            if gesture.moved {
+                pan = touch.delta();
+            }
+        }
+        [a, b, ..] => {
+            gesture.moved = true;
+            let distance = a.position().distance(b.position());
+            let previous = a.previous_position().distance(b.previous_position());
+            if on_map && distance > 0.0 && previous > 0.0 {
+                let centre = (a.position() + b.position()) / 2.0;
+                controller.zoom_by(previous / distance, Some(centre));
+            }
+            pan = (a.delta() + b.delta()) / 2.0;
+        }
+        [] => {}
+    }
+
+    if on_map && pan != Vec2::ZERO {
+        controller.follow = false;
+        for (mut transform, projection) in &mut cameras {
+            // Screen y grows downwards, world y upwards.
+            transform.translation -= (Vec2::new(pan.x, -pan.y) * projection.scale).extend(0.0);
+        }
+    }
+
+    for touch in touches.iter_just_released() {
+        let id = touch.id();
+        let held = gesture.started.remove(&id).map_or(0.0, |start| now - start);
+        if !gesture.ignored.remove(&id) && !gesture.moved && held < TAP_SECONDS {
+            tap.position = Some(touch.position());
+        }
+    }
+    for touch in touches.iter_just_canceled() {
+        gesture.started.remove(&touch.id());
+        gesture.ignored.remove(&touch.id());
+    }
+    if touches.iter().next().is_none() {
+        gesture.moved = false;
+    }
+}
+
+fn spawn_dpad(mut commands: Commands) {
+    let button = |direction: IVec2| {
+        (
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(DPAD_BUTTON),
+                    height: Val::Px(DPAD_BUTTON),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                border_color: BorderColor(Color::BLACK),
+                background_color: DPAD_RELEASED.into(),
+                ..default()
+            },
+            DPadButton(direction),
+        )
+    };
+    let gap = || NodeBundle {
+        style: Style {
+            width: Val::Px(DPAD_BUTTON),
+            height: Val::Px(DPAD_BUTTON),
+            ..default()
+        },
+        ..default()
+    };
+
+    // Three by three grid with the arrows around an empty middle.
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    right: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    width: Val::Px(DPAD_BUTTON * 3.0),
+                    position_type: PositionType::Absolute,
+                    flex_wrap: FlexWrap::Wrap,
+                    ..default()
+                },
+                // Only shown once the screen has been touched.
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            DPad,
+        ))
+        .with_children(|parent| {
+            parent.spawn(gap());
+            parent.spawn(button(IVec2::Y));
+            parent.spawn(gap());
+            parent.spawn(button(IVec2::NEG_X));
+            parent.spawn(gap());
+            parent.spawn(button(IVec2::X));
+            parent.spawn(gap());
+            parent.spawn(button(IVec2::NEG_Y));
+            parent.spawn(gap());
+        });
+}
+
+fn show_dpad(touches: Res<Touches>, mut dpads: Query<&mut Visibility, With<DPad>>) {
+    if touches.iter_just_pressed().next().is_none() {
+        return;
+    }
+    for mut visibility in &mut dpads {
+        if *visibility != Visibility::Inherited {
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
+
+fn read_dpad(
+    mut dpad: ResMut<VirtualDPad>,
+    mut buttons: Query<(&Interaction, &DPadButton, &mut BackgroundColor)>,
+) {
+    let mut direction = IVec2::ZERO;
+    for (interaction, button, mut color) in &mut buttons {
+        let pressed = *interaction == Interaction::Pressed;
+        if pressed {
+            direction += button.0;
+        }
+        let wanted = if pressed { DPAD_PRESSED } else { DPAD_RELEASED };
+        if color.0 != wanted {
+            color.0 = wanted;
+        }
+    }
+    if dpad.0 != direction {
+        dpad.0 = direction;
+    }
+}
@@ -0,0 +1,244 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    build::{hovered_cell, not_building, BuildMode, Settlements},
+    chain::ledger::{shorten_address, LocalPlayer},
+    fog::Fog,
+    map::{cell_to_world, Terrain, TILE_SIZE},
+    settlement::WorldObjects,
+    AppState,
+};
+
+/// Drawn over the fog, so unexplored tiles can be pointed at too, but under the player.
+const HIGHLIGHT_Z: f32 = 0.98;
+const HIGHLIGHT_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.25);
+
+/// Gap between the cursor and the tooltip, in screen pixels.
+const TOOLTIP_OFFSET: f32 = 16.0;
+const TOOLTIP_BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.75);
+
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inspected>()
+            .add_systems(Startup, spawn_inspector)
+            .add_systems(
+                Update,
+                (track_hover, move_highlight, fill_tooltip)
+                    .chain()
+                    .run_if(in_state(AppState::Finished)),
+            )
+            .add_systems(OnExit(AppState::Finished), hide_inspector);
+    }
+}
+
+/// Tile under the cursor and where on the screen the cursor is.
+#[derive(Resource, Default, PartialEq)]
+struct Inspected {
+    cell: Option<IVec2>,
+    cursor: Vec2,
+}
+
+#[derive(Component)]
+struct Highlight;
+
+#[derive(Component)]
+struct Tooltip;
+
+#[derive(Component)]
+struct TooltipText;
+
+fn spawn_inspector(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: HIGHLIGHT_COLOR,
+                custom_size: Some(Vec2::splat(TILE_SIZE)),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        Highlight,
+    ));
+
+    // No `Interaction`, so the tooltip never keeps clicks from the map under it.
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    padding: UiRect::all(Val::Px(6.0)),
+                    ..default()
+                },
+                background_color: TOOLTIP_BACKGROUND.into(),
+                z_index: ZIndex::Global(5),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Tooltip,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("PoetsenOne-Regular.ttf"),
+                        font_size: 18.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ),
+                TooltipText,
+            ));
+        });
+}
+
+fn track_hover(
+    mode: Res<BuildMode>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    interactions: Query<&Interaction>,
+    mut inspected: ResMut<Inspected>,
+) {
+    let cursor = windows.get_single().ok().and_then(Window::cursor_position);
+    // While building, the placement preview marks the tile instead.
+    let cell = if not_building(mode) {
+        hovered_cell(&windows, &cameras, &interactions)
+    } else {
+        None
+    };
+    inspected.set_if_neq(Inspected {
+        cell,
+        cursor: cursor.unwrap_or_default(),
+    });
+}
+
+fn move_highlight(
+    inspected: Res<Inspected>,
+    mut highlights: Query<(&mut Transform, &mut Visibility), With<Highlight>>,
+) {
+    if !inspected.is_changed() {
+        return;
+    }
+    for (mut transform, mut visibility) in &mut highlights {
+        let Some(cell) = inspected.cell else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Visible;
+        transform.translation = cell_to_world(cell).extend(HIGHLIGHT_Z);
+    }
+}
+
+/// Describes the hovered tile next to the cursor, on the side facing the middle of the
+/// window so it stays on screen.
+#[allow(clippy::too_many_arguments)]
+fn fill_tooltip(
+    inspected: Res<Inspected>,
+    terrain: Terrain,
+    fog: Res<Fog>,
+    world: Res<WorldObjects>,
+    settlements: Res<Settlements>,
+    local_player: Res<LocalPlayer>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut tooltips: Query<(&mut Style, &mut Visibility), With<Tooltip>>,
+    mut texts: Query<&mut Text, With<TooltipText>>,
+) {
+    let (Some(cell), Ok(window)) = (inspected.cell, windows.get_single()) else {
+        for (_, mut visibility) in &mut tooltips {
+            *visibility = Visibility::Hidden;
+        }
+        return;
+    };
+
+    let description = describe(cell, &terrain, &fog, &world, &settlements, &local_player);
+    for mut text in &mut texts {
+        if text.sections[0].value != description {
+            text.sections[0].value = description.clone();
+        }
+    }
+
+    let cursor = inspected.cursor;
+    let (left, right) = if cursor.x < window.width() / 2.0 {
+        (Val::Px(cursor.x + TOOLTIP_OFFSET), Val::Auto)
+    } else {
+        (
+            Val::Auto,
+            Val::Px(window.width() - cursor.x + TOOLTIP_OFFSET),
+        )
+    };
+    let (top, bottom) = if cursor.y < window.height() / 2.0 {
+        (Val::Px(cursor.y + TOOLTIP_OFFSET), Val::Auto)
+    } else {
+        (
+            Val::Auto,
+            Val::Px(window.height() - cursor.y + TOOLTIP_OFFSET),
+        )
+    };
+    for (mut style, mut visibility) in &mut tooltips {
+        *visibility = Visibility::Visible;
+        if [style.left, style.right, style.top, style.bottom] != [left, right, top, bottom] {
+            [style.left, style.right, style.top, style.bottom] = [left, right, top, bottom];
+        }
+    }
+}
+
+/// Coordinates, terrain and structure of `cell`; only the coordinates of tiles the
+/// player has not explored yet.
+fn describe(
+    cell: IVec2,
+    terrain: &Terrain,
+    fog: &Fog,
+    world: &WorldObjects,
+    settlements: &Settlements,
+    local_player: &LocalPlayer,
+) -> String {
+    let mut lines = vec![format!("Tile {}, {}", cell.x, cell.y)];
+    let (Some(biome), Some(climate)) = (terrain.biome(cell), terrain.climate(cell)) else {
+        return lines.join("\n");
+    };
+    if !fog.is_explored(cell) {
+        lines.push("Unexplored".to_string());
+        return lines.join("\n");
+    }
+
+    lines.push(biome.name().to_string());
+    lines.push(format!("Elevation {:.2}", climate.elevation));
+    lines.push(format!("Moisture {:.2}", climate.moisture));
+    lines.push(format!("Temperature {:.2}", climate.temperature));
+
+    let owner = |address: &str| {
+        let name = shorten_address(address);
+        if local_player.is(address) {
+            format!("Owner {name} (you)")
+        } else {
+            format!("Owner {name}")
+        }
+    };
+    if let Some(object) = world.at(cell) {
+        lines.push(format!(
+            "{} level {}",
+            object.structure.name(),
+            object.level
+        ));
+        lines.push(owner(&object.owner));
+    } else if let Some(kind) = settlements.get(cell) {
+        lines.push(format!("{} (not yet on chain)", kind.name()));
+        if let Some(address) = &local_player.0 {
+            lines.push(owner(address));
+        }
+    }
+    lines.join("\n")
+}
+
+fn hide_inspector(
+    mut inspected: ResMut<Inspected>,
+    mut highlights: Query<&mut Visibility, With<Highlight>>,
+    mut tooltips: Query<&mut Visibility, (With<Tooltip>, Without<Highlight>)>,
+) {
+    *inspected = Inspected::default();
+    for mut visibility in highlights.iter_mut().chain(tooltips.iter_mut()) {
+        *visibility = Visibility::Hidden;
+    }
+}
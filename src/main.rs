@@ -1,25 +1,36 @@
-use std::cell::RefCell;
-
 use bevy::{
     asset::AssetMetaCheck,
     log::{Level, LogPlugin},
     prelude::*,
 };
-use noise::{
-    utils::{NoiseMapBuilder, PlaneMapBuilder},
-    Perlin, Terrace,
-};
-use rand::{thread_rng, Rng};
-use wasm_bindgen::prelude::*;
 
 pub mod ui;
 
+mod build;
 mod chain;
+mod controls;
+mod fog;
 mod helpers;
+mod inspector;
+mod map;
+mod roster;
+mod round;
+mod settlement;
+mod travel;
+
+use chain::bridge::{GameEvent, MapSeedReceived};
+use controls::Action;
+use map::{
+    cell_to_world,
+    palette::{PaletteHandle, TerrainPalette},
+    Terrain, WorldNoise,
+};
 
-use chain::{ONCHAIN_MAP_SEED};
+/// Draw order of the player sprite, above the terrain chunk meshes at z = 0.
+const PLAYER_Z: f32 = 1.0;
 
-const NOISEMAP_SIZE: (usize, usize) = (100usize, 100usize);
+/// Seconds between two steps while a movement key is held.
+const MOVE_COOLDOWN: f32 = 0.15;
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, States)]
 pub enum AppState {
@@ -30,9 +41,8 @@ pub enum AppState {
     Ledger,
 }
 
-
 #[derive(Resource)]
-struct Seed(u32);
+pub struct Seed(pub u32);
 
 #[derive(Resource, Deref)]
 struct Root(Entity);
@@ -40,9 +50,10 @@ struct Root(Entity);
 #[derive(Default)]
 struct Player {
     entity: Option<Entity>,
-    pos_x: f32,
-    pos_y: f32,
+    cell: IVec2,
     move_cooldown: Timer,
+    /// Tile `roster` last placed the player on; `None` until the map is ready.
+    spawn: Option<IVec2>,
 }
 
 #[derive(Resource, Default)]
@@ -50,107 +61,57 @@ struct Game {
     player: Player,
 }
 
-// TODO: noise generator from seed.
-fn generate_random_map() -> noise::utils::NoiseMap {
-    let mut rng = thread_rng();
-    let seed: u32 = rng.gen();
-
-    generate_random_map_with_seed(seed)
-}
-
-
-pub struct OnChainPlayer {
-    pub address: String,
-    pub score: String,
-}
-
-/// Creates a procederally-generated map using noise.
-fn generate_random_map_with_seed(seed: u32) -> noise::utils::NoiseMap {
-    let perlin = Perlin::new(seed);
-
-    let terrace_inverted: Terrace<f64, Perlin, 2> = Terrace::new(perlin)
-        .add_control_point(-1.0)
-        .add_control_point(-0.5)
-        .add_control_point(0.1)
-        .add_control_point(1.0)
-        .invert_terraces(true);
-
-    PlaneMapBuilder::new(terrace_inverted)
-        .set_size(NOISEMAP_SIZE.0, NOISEMAP_SIZE.1)
-        .build()
-}
-
-fn get_color(val: f64) -> Color {
-    let color_result = match val.abs() {
-        v if v < 0.1 => Color::hex("#0a7e0a"),
-        v if v < 0.2 => Color::hex("#0da50d"),
-        v if v < 0.3 => Color::hex("#10cb10"),
-        v if v < 0.4 => Color::hex("#18ed18"),
-        v if v < 0.5 => Color::hex("#3ff03f"),
-        v if v < 0.6 => Color::hex("#65f365"),
-        v if v < 0.7 => Color::hex("#8cf68c"),
-        v if v < 0.8 => Color::hex("#b2f9b2"),
-        v if v < 0.9 => Color::hex("#d9fcd9"),
-        v if v <= 1.0 => Color::hex("#ffffff"),
-        _ => panic!("unexpected value"),
-    };
-    color_result.expect("Getting color from HEX error")
-}
-
-fn get_tile(val: f64, asset_server: &Res<AssetServer>) -> Handle<Image> {
-    match val.abs() {
-        v if v < 0.1 => asset_server.load("Tile/grass.png"),
-        v if v < 0.2 => asset_server.load("Tile/trees.png"),
-        v if v < 0.3 => asset_server.load("Tile/grass_1.png"),
-        // v if v < 0.4 => Color::hex("#18ed18"),
-        // v if v < 0.5 => Color::hex("#3ff03f"),
-        // v if v < 0.6 => Color::hex("#65f365"),
-        v if v < 0.7 => asset_server.load("Tile/water.png"),
-        v if v < 0.8 => asset_server.load("Tile/trees_1.png"),
-        v if v < 0.9 => asset_server.load("Tile/forest.png"),
-        v if v <= 1.0 => asset_server.load("Tile/sand.png"),
-        _ => asset_server.load("Tile/medievalTile_41.png"),
-    }
-}
-
+/// Steps the player one tile per cooldown tick while a move action or the on-screen d-pad
+/// is held, refusing tiles the palette marks as not walkable or outside its `world_radius`.
 fn player_movement(
-    mut commands: Commands,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    actions: Res<controls::Actions>,
+    dpad: Res<helpers::touch::VirtualDPad>,
+    terrain: Terrain,
     mut game: ResMut<Game>,
+    mut route: ResMut<travel::Route>,
     mut transforms: Query<&mut Transform>,
 ) {
-    let mut moved = false;
-    let mut rotation = 0.0;
+    game.player.move_cooldown.tick(time.delta());
+    if !game.player.move_cooldown.finished() {
+        return;
+    }
 
-    if keyboard_input.pressed(KeyCode::ArrowUp) {
-        game.player.pos_y += 1f32;
-        // rotation = -PI / 2.;
-        moved = true;
+    let mut step = IVec2::ZERO;
+    if actions.pressed(Action::MoveUp) {
+        step.y += 1;
     }
-    if keyboard_input.pressed(KeyCode::ArrowDown) {
-        game.player.pos_y -= 1f32;
-        // rotation = PI / 2.;
-        moved = true;
+    if actions.pressed(Action::MoveDown) {
+        step.y -= 1;
     }
-    if keyboard_input.pressed(KeyCode::ArrowRight) {
-        game.player.pos_x += 1f32;
-        // rotation = PI;
-        moved = true;
+    if actions.pressed(Action::MoveRight) {
+        step.x += 1;
     }
-    if keyboard_input.pressed(KeyCode::ArrowLeft) {
-        game.player.pos_x -= 1f32;
-        // rotation = 0.0;
-        moved = true;
+    if actions.pressed(Action::MoveLeft) {
+        step.x -= 1;
     }
+    step = (step + dpad.0).clamp(IVec2::NEG_ONE, IVec2::ONE);
+    if step == IVec2::ZERO {
+        return;
+    }
+
+    // Slide along obstacles when a diagonal step is blocked.
+    let from = game.player.cell;
+    let Some(target) = [step, IVec2::new(step.x, 0), IVec2::new(0, step.y)]
+        .into_iter()
+        .filter(|s| *s != IVec2::ZERO)
+        .map(|s| from + s)
+        .find(|cell| terrain.is_walkable(*cell))
+    else {
+        return;
+    };
 
     // move on the board
-    if moved {
-        game.player.move_cooldown.reset();
-        *transforms.get_mut(game.player.entity.unwrap()).unwrap() = Transform {
-            translation: Vec3::new(game.player.pos_x as f32, game.player.pos_y, 0f32),
-            rotation: Quat::from_rotation_y(rotation),
-            ..default()
-        };
+    route.cancel();
+    game.player.cell = target;
+    game.player.move_cooldown.reset();
+    if let Some(mut transform) = game.player.entity.and_then(|e| transforms.get_mut(e).ok()) {
+        transform.translation = cell_to_world(target).extend(PLAYER_Z);
     }
 }
 
@@ -158,49 +119,37 @@ fn generate_world(
     mut commands: Commands,
     mut next_state: ResMut<NextState<AppState>>,
     asset_server: Res<AssetServer>,
+    seed: Res<Seed>,
+    palettes: Res<Assets<TerrainPalette>>,
+    palette: Res<PaletteHandle>,
     mut game: ResMut<Game>,
 ) {
-    let seed = ONCHAIN_MAP_SEED.with(|s| s.borrow().to_owned());
-
-    let map = generate_random_map_with_seed(seed);
-    let (grid_width, grid_height) = map.size();
-
-    let tile_size = 32_f32;
+    let rules = palette.get(&palettes).biomes.clone();
+    let noise = WorldNoise::new(seed.0, rules);
+
+    // Lets players compare their map with other clients for the same seed.
+    let fingerprint = noise.grid((-50, -50), 100, 100).fingerprint();
+    info!(
+        "Map seed {} has terrain fingerprint {:#018x}",
+        seed.0, fingerprint
+    );
 
-    let start_x = -(grid_width as f32) * tile_size / 2.0;
-    let start_y = -(grid_height as f32) * tile_size / 2.0;
+    // Terrain chunks are streamed in around the view by `map::MapPlugin`.
+    commands.insert_resource(noise);
 
-    let root = commands
-        .spawn(SpatialBundle::default())
-        .with_children(|parent| {
-            for col_x in 0..grid_width {
-                for col_y in 0..grid_height {
-                    let val = map.get_value(col_x, col_y);
-                    // if val > 0.8_f64 {
-                    // debug!("Value for {}:{} = {}", col_x, col_y, val);
-                    // }
-                    let x = start_x + col_x as f32 * tile_size;
-                    let y = start_y + col_y as f32 * tile_size;
+    let root = commands.spawn(SpatialBundle::default()).id();
 
-                    parent.spawn(SpriteBundle {
-                        sprite: Sprite {
-                            // color: get_tile(val, asset_server),
-                            custom_size: Some(Vec2::new(tile_size, tile_size)),
-                            ..default()
-                        },
-                        texture: get_tile(val, &asset_server),
-                        transform: Transform::from_translation(Vec3::new(x, y, 0.)),
-                        ..default()
-                    });
-                }
-            }
-        })
-        .id();
+    // Replace the player left over from a previous map.
+    if let Some(entity) = game.player.entity.take() {
+        commands.entity(entity).despawn_recursive();
+    }
 
-    let starting_player_pos = GlobalTransform::default().translation_vec3a();
+    // Placed on its spawn tile by `roster` once the terrain can be queried.
+    let starting_player_pos = cell_to_world(IVec2::ZERO);
 
-    game.player.pos_x = starting_player_pos.x;
-    game.player.pos_y = starting_player_pos.y;
+    game.player.cell = IVec2::ZERO;
+    game.player.spawn = None;
+    game.player.move_cooldown = Timer::from_seconds(MOVE_COOLDOWN, TimerMode::Once);
 
     game.player.entity = Some(
         // Spawn Player
@@ -211,6 +160,12 @@ fn generate_world(
                     ..default()
                 },
                 texture: asset_server.load("Unit/player.png"),
+                transform: Transform::from_xyz(
+                    starting_player_pos.x,
+                    starting_player_pos.y,
+                    PLAYER_Z,
+                ),
+                visibility: Visibility::Hidden,
                 ..default()
             })
             .id(),
@@ -225,20 +180,30 @@ fn generate_world(
 
 // Create player sprite
 // allow movement of player
-pub fn onchain_events(mut commands: Commands, mut next_state: ResMut<NextState<AppState>>) {
-    ONCHAIN_MAP_SEED.with(|seed| {
-        let seed = seed.borrow();
-        if *seed != 0 {
-            dbg!("Seed is set to {}", *seed);
-            commands.insert_resource(Seed(*seed));
-            next_state.set(AppState::Build);
-        }
-    });
-    // If resource is set, then do something here!
+pub fn onchain_events(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut seeds: EventReader<MapSeedReceived>,
+    current_seed: Option<Res<Seed>>,
+) {
+    let Some(MapSeedReceived(seed)) = seeds.read().last() else {
+        return;
+    };
+    if current_seed.is_some_and(|s| s.0 == *seed) {
+        return;
+    }
+
+    info!("Seed is set to {}", seed);
+    commands.insert_resource(Seed(*seed));
+    next_state.set(AppState::Build);
 }
 
-pub fn cleanup(mut commands: Commands, root: Res<Root>) {
-    commands.entity(**root).despawn_recursive();
+/// Removes the previous map's chunks before a reset or a new seed builds the next one.
+/// Opening the ledger keeps them, so the map is still there when it is closed.
+pub fn cleanup(mut commands: Commands, root: Option<Res<Root>>) {
+    if let Some(root) = root {
+        commands.entity(**root).despawn_recursive();
+    }
 }
 
 fn reset(mut events: EventReader<ui::ResetMapEvent>, mut next_state: ResMut<NextState<AppState>>) {
@@ -250,208 +215,27 @@ fn reset(mut events: EventReader<ui::ResetMapEvent>, mut next_state: ResMut<Next
 fn ledger_menu(
     mut events: EventReader<ui::ShowLedgerEvent>,
     mut next_state: ResMut<NextState<AppState>>,
+    mut game_events: EventWriter<GameEvent>,
 ) {
     for e in events.read() {
-        if e.0 == true {
-            println!("Ledger event");
+        if e.0 {
+            debug!("Opening the ledger");
             next_state.set(AppState::Ledger);
+            game_events.send(GameEvent::LedgerOpened);
         }
     }
 }
 
 fn exit_ledger_menu(
-    mut commands: Commands,
     mut events: EventReader<ui::ShowLedgerEvent>,
     mut next_state: ResMut<NextState<AppState>>,
-    table_root: Res<TableRoot>,
+    mut game_events: EventWriter<GameEvent>,
 ) {
-    dbg!("EXIT LEDGER MENU");
     for e in events.read() {
-        if e.0 == false {
-            commands.entity(table_root.0).despawn_recursive();
+        if !e.0 {
+            debug!("Closing the ledger");
             next_state.set(AppState::Finished);
-        }
-    }
-}
-
-#[derive(Resource)]
-struct TableRoot(Entity);
-
-impl Default for TableRoot {
-    fn default() -> Self {
-        TableRoot(Entity::from_raw(0))
-    }
-}
-
-#[derive(Clone, Copy)]
-enum Tables {
-    EconomicVictory,
-    CulturalVictory,
-    DiplomaticVictory,
-}
-
-impl ToString for Tables {
-    fn to_string(&self) -> String {
-        match self {
-            Tables::EconomicVictory => "Economic Victory".to_string(),
-            Tables::CulturalVictory => "Cultural Victory".to_string(),
-            Tables::DiplomaticVictory => "Diplomatic Victory".to_string(),
-        }
-    }
-}
-
-impl From<usize> for Tables {
-    fn from(i: usize) -> Self {
-        match i {
-            0 => Tables::EconomicVictory,
-            1 => Tables::CulturalVictory,
-            2 => Tables::DiplomaticVictory,
-            _ => Tables::EconomicVictory,
-        }
-    }
-}
-
-#[derive(Component)]
-struct Tab {
-    table_id: Tables,
-}
-
-#[derive(Component)]
-struct TableRow {
-    table_id: Tables,
-}
-
-// Economic Victory, Cultural Victory, Diplomatic Victory
-fn show_ledger(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let table_root = commands
-        .spawn(NodeBundle {
-            style: Style {
-                width: Val::Percent(50.0),
-                height: Val::Percent(80.0),
-                left: Val::Percent(25.0),
-                top: Val::Percent(10.0),
-                bottom: Val::Percent(10.0),
-                right: Val::Percent(25.0),
-                flex_direction: FlexDirection::Column,
-                align_items: AlignItems::Center,
-                justify_content: JustifyContent::FlexStart,
-                border: UiRect {
-                    left: Val::Px(2.0),
-                    right: Val::Px(2.0),
-                    top: Val::Px(2.0),
-                    bottom: Val::Px(2.0),
-                },
-                ..default()
-            },
-            background_color: Color::DARK_GRAY.into(),
-            border_color: Color::WHITE.into(),
-            ..default()
-        })
-        .id();
-
-    commands.insert_resource(TableRoot(table_root));
-
-    commands.entity(table_root).with_children(|parent| {
-        // Header
-        parent.spawn(TextBundle {
-            text: Text::from_section(
-                "Ledger Stats",
-                TextStyle {
-                    font: asset_server.load("PoetsenOne-Regular.ttf"),
-                    font_size: 30.0,
-                    color: Color::WHITE,
-                },
-            ),
-            ..default()
-        });
-
-        // Tabs for switching tables
-        parent
-            .spawn(NodeBundle {
-                style: Style {
-                    width: Val::Px(300.0),
-                    height: Val::Px(50.0),
-                    flex_direction: FlexDirection::Row,
-                    align_items: AlignItems::Center,
-                    justify_content: JustifyContent::SpaceBetween,
-                    ..default()
-                },
-                ..default()
-            })
-            .with_children(|tabs| {
-                for i in 0..3 {
-                    tabs.spawn(ButtonBundle {
-                        style: Style {
-                            width: Val::Percent(33.0),
-                            height: Val::Percent(100.0),
-                            // Styling for each tab button
-                            ..default()
-                        },
-                        background_color: Color::rgb(0.15, 0.15, 0.15).into(),
-                        // color: Color::rgb(0.15, 0.15, 0.15).into(),
-                        ..default()
-                    })
-                    .insert(Tab {
-                        table_id: Tables::from(i),
-                    })
-                    .with_children(|button| {
-                        button.spawn(TextBundle {
-                            text: Text::from_section(
-                                Tables::from(i).to_string(),
-                                TextStyle {
-                                    font: asset_server.load("PoetsenOne-Regular.ttf"),
-                                    font_size: 20.0,
-                                    color: Color::WHITE,
-                                },
-                            ),
-                            ..default()
-                        });
-                    });
-                }
-            });
-
-        spawn_rows(Tables::EconomicVictory, parent, &asset_server);
-    });
-}
-
-fn spawn_rows(table_id: Tables, parent: &mut ChildBuilder, asset_server: &Res<AssetServer>) {
-    for i in 0..5 {
-        parent
-            .spawn(TextBundle {
-                text: Text::from_section(
-                    format!("Row {} of Table {}", i + 1, table_id.to_string()),
-                    TextStyle {
-                        font: asset_server.load("PoetsenOne-Regular.ttf"),
-                        font_size: 20.0,
-                        color: Color::GRAY,
-                    },
-                ),
-                ..default()
-            })
-            .insert(TableRow { table_id });
-    }
-}
-
-fn tab_click_system(
-    mut commands: Commands,
-    table_root: Res<TableRoot>,
-    mut interaction_query: Query<(&Interaction, &Tab), (Changed<Interaction>, With<Button>)>,
-    mut row_query: Query<(Entity, &TableRow)>,
-    asset_server: Res<AssetServer>,
-) {
-    for (interaction, tab) in interaction_query.iter() {
-        if *interaction == Interaction::Pressed {
-            // Clear existing rows
-            for (entity, row) in row_query.iter_mut() {
-                // if row.table_id == tab.table_id {
-                commands.entity(entity).despawn_recursive();
-                // }
-            }
-
-            // Spawn new rows based on the tab clicked
-            commands.entity(table_root.0).with_children(|parent| {
-                spawn_rows(tab.table_id, parent, &asset_server);
-            });
+            game_events.send(GameEvent::LedgerClosed);
         }
     }
 }
@@ -476,22 +260,35 @@ fn main() {
                     }),
                     ..default()
                 }),
+            build::BuildPlugin,
             helpers::camera::CameraPlugin,
+            helpers::touch::TouchPlugin,
+            chain::bridge::BridgePlugin,
+            chain::ledger::LedgerPlugin,
+            controls::ControlsPlugin,
+            fog::FogPlugin,
+            inspector::InspectorPlugin,
+            map::MapPlugin,
+            roster::RosterPlugin,
+            round::RoundPlugin,
+            settlement::SettlementPlugin,
+            travel::TravelPlugin,
             ui::UiPlugin,
         ))
         .init_resource::<Game>()
-        .init_resource::<TableRoot>()
         .init_state::<AppState>()
         .add_systems(Update, onchain_events)
-        .add_systems(Update, tab_click_system)
-        .add_systems(OnEnter(AppState::Build), generate_world)
-        .add_systems(OnEnter(AppState::Ledger), show_ledger)
+        .add_systems(OnEnter(AppState::Build), (cleanup, generate_world).chain())
         .add_systems(
             Update,
-            (player_movement, reset.run_if(in_state(AppState::Finished))),
+            (
+                player_movement
+                    .run_if(in_state(AppState::Finished))
+                    .run_if(round::round_open),
+                reset.run_if(in_state(AppState::Finished)),
+            ),
         )
         .add_systems(Update, ledger_menu.run_if(in_state(AppState::Finished)))
         .add_systems(Update, exit_ledger_menu.run_if(in_state(AppState::Ledger)))
-        .add_systems(OnExit(AppState::Finished), cleanup)
         .run();
 }
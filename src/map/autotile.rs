@@ -0,0 +1,214 @@
+//! Transition tiles picked from each cell's neighbours.
+//!
+//! Biomes alone give hard seams between water, sand and forest. This pass looks at the
+//! cells around each tile so coastlines get a continuous beach and forests thin out
+//! towards their edges instead of stopping at a straight line. Beaches are drawn on
+//! the grass along each edge and corner facing water, so they follow the coast in
+//! every direction.
+
+use super::{biome::Biome, terrain::TerrainGrid};
+
+/// What to draw for one tile, independent of the texture used for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TileVariant {
+    /// Plain tile of the cell's biome.
+    Base(Biome),
+    /// Grassland bordering open water: grass with beach along the sides facing it.
+    Shore(Sides),
+    /// Forest tile thinned towards the forest's edge, from `0` (sparsest) up to `2`.
+    ForestEdge(u8),
+}
+
+/// Edges and corners of a tile, one bit each, numbered in quarter turns clockwise:
+/// edges from north, corners from the north east one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Sides {
+    pub edges: u8,
+    pub corners: u8,
+}
+
+impl Sides {
+    pub const NONE: Sides = Sides {
+        edges: 0,
+        corners: 0,
+    };
+
+    /// Quarter turns from north of every edge in the set.
+    pub fn edges(self) -> impl Iterator<Item = u8> {
+        (0..4).filter(move |turns| self.edges & 1 << turns != 0)
+    }
+
+    /// Quarter turns from north east of every corner in the set.
+    pub fn corners(self) -> impl Iterator<Item = u8> {
+        (0..4).filter(move |turns| self.corners & 1 << turns != 0)
+    }
+}
+
+/// North, east, south and west.
+const EDGES: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+
+/// North east, south east, south west and north west.
+const CORNERS: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+
+/// Edge neighbours followed by the four diagonals.
+const SURROUNDING: [(i32, i32); 8] = [
+    (0, 1),
+    (1, 0),
+    (0, -1),
+    (-1, 0),
+    (1, 1),
+    (1, -1),
+    (-1, -1),
+    (-1, 1),
+];
+
+fn is_water(biome: Biome) -> bool {
+    matches!(biome, Biome::Ocean | Biome::Lake)
+}
+
+/// Sides of `(x, y)` that touch water. Corners are only set where neither edge next
+/// to them is, as the beach along those edges already covers the corner.
+fn water_sides(grid: &TerrainGrid, x: i32, y: i32) -> Sides {
+    let wet = |(dx, dy): (i32, i32)| grid.get(x + dx, y + dy).is_some_and(is_water);
+    let mut sides = Sides::NONE;
+    for (turns, offset) in EDGES.into_iter().enumerate() {
+        if wet(offset) {
+            sides.edges |= 1 << turns;
+        }
+    }
+    for (turns, offset) in CORNERS.into_iter().enumerate() {
+        // Corner `turns` lies between edge `turns` and the next one clockwise.
+        let next = (turns + 1) % 4;
+        if wet(offset) && sides.edges & (1 << turns | 1 << next) == 0 {
+            sides.corners |= 1 << turns;
+        }
+    }
+    sides
+}
+
+/// Picks the tile for cell `(x, y)`, or `None` outside the grid.
+///
+/// Neighbours outside the grid are ignored, so callers should classify a one tile
+/// border around the area they draw.
+pub fn variant(grid: &TerrainGrid, x: i32, y: i32) -> Option<TileVariant> {
+    let biome = grid.get(x, y)?;
+    let around = |offsets: &'static [(i32, i32)]| {
+        offsets
+            .iter()
+            .filter_map(move |(dx, dy)| grid.get(x + dx, y + dy))
+    };
+
+    let variant = match biome {
+        // Only grassland: beach on forest would look walkable when it is not.
+        Biome::Grassland => match water_sides(grid, x, y) {
+            Sides::NONE => TileVariant::Base(Biome::Grassland),
+            sides => TileVariant::Shore(sides),
+        },
+        Biome::Forest => match around(&SURROUNDING).filter(|b| *b == Biome::Forest).count() {
+            8 => TileVariant::Base(Biome::Forest),
+            6 | 7 => TileVariant::ForestEdge(2),
+            4 | 5 => TileVariant::ForestEdge(1),
+            _ => TileVariant::ForestEdge(0),
+        },
+        _ => TileVariant::Base(biome),
+    };
+
+    Some(variant)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Grid from rows of biome letters, top row first, with its bottom left cell at
+    /// the origin: `~` ocean, `o` lake, `s` sand, `g` grassland, `f` forest, `t` tundra.
+    fn terrain(rows: &[&str]) -> TerrainGrid {
+        let width = rows[0].len();
+        let tiles = rows
+            .iter()
+            .rev()
+            .flat_map(|row| row.chars())
+            .map(|c| match c {
+                '~' => Biome::Ocean,
+                'o' => Biome::Lake,
+                's' => Biome::Sand,
+                'g' => Biome::Grassland,
+                'f' => Biome::Forest,
+                't' => Biome::Tundra,
+                _ => panic!("unknown biome {c:?}"),
+            })
+            .collect();
+        TerrainGrid::from_tiles((0, 0), width, rows.len(), tiles)
+    }
+
+    fn edges(turns: &[u8]) -> Sides {
+        Sides {
+            edges: turns.iter().map(|turn| 1 << turn).sum(),
+            corners: 0,
+        }
+    }
+
+    #[test]
+    fn shore_faces_the_water() {
+        let grid = terrain(&["~~~", "ggg", "ggg"]);
+        assert_eq!(variant(&grid, 1, 1), Some(TileVariant::Shore(edges(&[0]))));
+        assert_eq!(
+            variant(&grid, 1, 0),
+            Some(TileVariant::Base(Biome::Grassland))
+        );
+
+        let grid = terrain(&["gg~", "gg~", "ggo"]);
+        assert_eq!(variant(&grid, 1, 1), Some(TileVariant::Shore(edges(&[1]))));
+        let grid = terrain(&["g~", "~g"]);
+        assert_eq!(
+            variant(&grid, 1, 0),
+            Some(TileVariant::Shore(edges(&[0, 3])))
+        );
+        assert_eq!(
+            variant(&grid, 0, 1),
+            Some(TileVariant::Shore(edges(&[1, 2])))
+        );
+    }
+
+    #[test]
+    fn diagonal_water_only_marks_an_open_corner() {
+        let grid = terrain(&["gg~", "ggg", "ggg"]);
+        let corner = Sides {
+            edges: 0,
+            corners: 1,
+        };
+        assert_eq!(variant(&grid, 1, 1), Some(TileVariant::Shore(corner)));
+
+        // The beach along the north edge already covers the corner.
+        let grid = terrain(&["g~~", "ggg"]);
+        assert_eq!(variant(&grid, 1, 0), Some(TileVariant::Shore(edges(&[0]))));
+    }
+
+    #[test]
+    fn only_grassland_gets_a_beach() {
+        let grid = terrain(&["~~~", "tfs", "fff"]);
+        assert_eq!(variant(&grid, 0, 1), Some(TileVariant::Base(Biome::Tundra)));
+        assert_eq!(variant(&grid, 2, 1), Some(TileVariant::Base(Biome::Sand)));
+        assert!(matches!(
+            variant(&grid, 1, 1),
+            Some(TileVariant::ForestEdge(_))
+        ));
+    }
+
+    #[test]
+    fn forests_thin_towards_their_edge() {
+        let grid = terrain(&["fffff", "fffff", "fffff", "ggggg"]);
+        assert_eq!(variant(&grid, 2, 2), Some(TileVariant::Base(Biome::Forest)));
+        assert_eq!(variant(&grid, 2, 1), Some(TileVariant::ForestEdge(1)));
+
+        let grid = terrain(&["ggg", "gfg", "ggg"]);
+        assert_eq!(variant(&grid, 1, 1), Some(TileVariant::ForestEdge(0)));
+    }
+
+    #[test]
+    fn outside_the_grid_is_none() {
+        let grid = terrain(&["gg", "gg"]);
+        assert_eq!(variant(&grid, 2, 0), None);
+        assert_eq!(variant(&grid, 0, -1), None);
+    }
+}
@@ -0,0 +1,197 @@
+//! Biome classification from layered climate noise.
+//!
+//! Elevation, moisture and temperature are sampled from independent seeded noise
+//! layers and matched against an ordered list of rules. The rules are plain data
+//! (the `biomes` section of `assets/terrain.palette.ron`) so they can be tuned without
+//! touching the generator.
+
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
+use serde::{Deserialize, Serialize};
+
+/// Palette whose rules every client uses unless told otherwise.
+pub const DEFAULT_PALETTE: &str = include_str!("../../assets/terrain.palette.ron");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Biome {
+    Ocean = 0,
+    Lake = 1,
+    Sand = 2,
+    Grassland = 3,
+    Forest = 4,
+    Tundra = 5,
+}
+
+impl Biome {
+    pub fn name(self) -> &'static str {
+        match self {
+            Biome::Ocean => "Ocean",
+            Biome::Lake => "Lake",
+            Biome::Sand => "Sand",
+            Biome::Grassland => "Grassland",
+            Biome::Forest => "Forest",
+            Biome::Tundra => "Tundra",
+        }
+    }
+}
+
+/// Climate noise values sampled at one tile, each roughly within `-1..=1`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Climate {
+    pub elevation: f64,
+    pub moisture: f64,
+    pub temperature: f64,
+}
+
+/// Half-open `min..max` range; a missing bound is unbounded.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Band {
+    #[serde(default = "Band::unbounded_min")]
+    pub min: f64,
+    #[serde(default = "Band::unbounded_max")]
+    pub max: f64,
+}
+
+impl Band {
+    fn unbounded_min() -> f64 {
+        f64::NEG_INFINITY
+    }
+
+    fn unbounded_max() -> f64 {
+        f64::INFINITY
+    }
+
+    pub fn contains(&self, value: f64) -> bool {
+        self.min <= value && value < self.max
+    }
+}
+
+impl Default for Band {
+    fn default() -> Self {
+        Band {
+            min: Band::unbounded_min(),
+            max: Band::unbounded_max(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BiomeRule {
+    pub biome: Biome,
+    #[serde(default)]
+    pub elevation: Band,
+    #[serde(default)]
+    pub moisture: Band,
+    #[serde(default)]
+    pub temperature: Band,
+}
+
+impl BiomeRule {
+    fn matches(&self, climate: &Climate) -> bool {
+        self.elevation.contains(climate.elevation)
+            && self.moisture.contains(climate.moisture)
+            && self.temperature.contains(climate.temperature)
+    }
+}
+
+/// Settings of one fractal noise layer.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LayerConfig {
+    /// Noise-space distance between neighbouring tiles.
+    pub frequency: f64,
+    pub octaves: usize,
+    /// Added to the map seed so each layer gets independent noise.
+    pub seed_offset: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClimateLayers {
+    pub elevation: LayerConfig,
+    pub moisture: LayerConfig,
+    pub temperature: LayerConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BiomeRules {
+    pub layers: ClimateLayers,
+    pub rules: Vec<BiomeRule>,
+    /// Biome used when no rule matches.
+    pub fallback: Biome,
+}
+
+impl BiomeRules {
+    pub fn classify(&self, climate: &Climate) -> Biome {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(climate))
+            .map_or(self.fallback, |rule| rule.biome)
+    }
+}
+
+impl Default for BiomeRules {
+    fn default() -> Self {
+        #[derive(Deserialize)]
+        struct Palette {
+            biomes: BiomeRules,
+        }
+
+        ron::from_str::<Palette>(DEFAULT_PALETTE)
+            .expect("assets/terrain.palette.ron is valid")
+            .biomes
+    }
+}
+
+struct Layer {
+    noise: Fbm<Perlin>,
+    frequency: f64,
+}
+
+impl Layer {
+    fn new(seed: u32, config: &LayerConfig) -> Self {
+        let seed = seed.wrapping_add(config.seed_offset);
+        let octaves = config.octaves.clamp(1, Fbm::<Perlin>::MAX_OCTAVES);
+
+        // `Fbm::new` seeds its octaves with `seed + i`, which overflows for seeds near
+        // `u32::MAX`; build the sources with wrapping seeds instead.
+        let sources = (0..octaves)
+            .map(|i| Perlin::new(seed.wrapping_add(i as u32)))
+            .collect();
+
+        Layer {
+            noise: Fbm::<Perlin>::new(0)
+                .set_octaves(octaves)
+                .set_sources(sources),
+            frequency: config.frequency,
+        }
+    }
+
+    fn sample(&self, x: i32, y: i32) -> f64 {
+        self.noise
+            .get([x as f64 * self.frequency, y as f64 * self.frequency])
+    }
+}
+
+/// The three seeded climate layers of a map.
+pub struct ClimateNoise {
+    elevation: Layer,
+    moisture: Layer,
+    temperature: Layer,
+}
+
+impl ClimateNoise {
+    pub fn new(seed: u32, layers: &ClimateLayers) -> Self {
+        ClimateNoise {
+            elevation: Layer::new(seed, &layers.elevation),
+            moisture: Layer::new(seed, &layers.moisture),
+            temperature: Layer::new(seed, &layers.temperature),
+        }
+    }
+
+    pub fn sample(&self, x: i32, y: i32) -> Climate {
+        Climate {
+            elevation: self.elevation.sample(x, y),
+            moisture: self.moisture.sample(x, y),
+            temperature: self.temperature.sample(x, y),
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use super::TILE_SIZE;
+
+/// Height between the texture layers of a tile, e.g. beach drawn over grass.
+const LAYER_STEP: f32 = 0.01;
+
+/// Marks the parent entity of one `CHUNK_SIZE` x `CHUNK_SIZE` block of tiles.
+#[derive(Component)]
+pub struct TileChunk {
+    pub coord: IVec2,
+}
+
+/// One material per tile texture, shared by every chunk so the meshes batch.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct TileMaterials(HashMap<String, Handle<ColorMaterial>>);
+
+#[derive(SystemParam)]
+pub struct TileAssets<'w> {
+    asset_server: Res<'w, AssetServer>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<ColorMaterial>>,
+    cache: ResMut<'w, TileMaterials>,
+}
+
+impl TileAssets<'_> {
+    fn material(&mut self, texture: &str) -> Handle<ColorMaterial> {
+        let TileAssets {
+            asset_server,
+            materials,
+            cache,
+            ..
+        } = self;
+
+        if let Some(material) = cache.get(texture) {
+            return material.clone();
+        }
+
+        let material = materials.add(ColorMaterial::from(
+            asset_server.load::<Image>(texture.to_string()),
+        ));
+        cache.insert(texture.to_string(), material.clone());
+        material
+    }
+}
+
+/// Builds a single mesh holding one textured quad per tile centre, each turned
+/// clockwise by its number of quarter turns.
+fn quad_mesh(quads: &[(Vec2, u8)]) -> Mesh {
+    let half = TILE_SIZE / 2.0;
+
+    let mut positions = Vec::with_capacity(quads.len() * 4);
+    let mut uvs = Vec::with_capacity(quads.len() * 4);
+    let mut indices = Vec::with_capacity(quads.len() * 6);
+
+    for (i, (centre, turns)) in quads.iter().enumerate() {
+        let base = (i * 4) as u32;
+
+        positions.extend([
+            [centre.x - half, centre.y - half, 0.0],
+            [centre.x + half, centre.y - half, 0.0],
+            [centre.x + half, centre.y + half, 0.0],
+            [centre.x - half, centre.y + half, 0.0],
+        ]);
+        // Corners in the same order as the positions; shifting them along turns the
+        // texture a quarter clockwise.
+        let mut corners = [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]];
+        corners.rotate_left(usize::from(*turns % 4));
+        uvs.extend(corners);
+        indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Spawns a chunk at `translation` with one mesh per distinct texture and layer.
+///
+/// `tiles` holds each tile's centre relative to the chunk origin and the textures it
+/// should be drawn with, bottom first, as given by `TerrainPalette::layers`.
+pub fn spawn_chunk(
+    parent: &mut ChildBuilder,
+    coord: IVec2,
+    translation: Vec3,
+    tiles: &[(Vec2, Vec<(&str, u8)>)],
+    assets: &mut TileAssets,
+) {
+    let mut batches: HashMap<(usize, &str), Vec<(Vec2, u8)>> = HashMap::new();
+    for (centre, layers) in tiles {
+        for (layer, (texture, turns)) in layers.iter().enumerate() {
+            batches
+                .entry((layer, texture))
+                .or_default()
+                .push((*centre, *turns));
+        }
+    }
+
+    parent
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_translation(translation)),
+            TileChunk { coord },
+        ))
+        .with_children(|chunk| {
+            for ((layer, texture), quads) in batches {
+                chunk.spawn(MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(assets.meshes.add(quad_mesh(&quads))),
+                    material: assets.material(texture),
+                    transform: Transform::from_xyz(0.0, 0.0, layer as f32 * LAYER_STEP),
+                    ..default()
+                });
+            }
+        });
+}
@@ -0,0 +1,235 @@
+//! Tiles the local player has seen, stored as one bit per tile in the chunks they
+//! have explored anything in.
+//!
+//! The web app persists the set between sessions (or on chain), so it has a compact
+//! byte encoding: a version byte followed by one entry per explored chunk in
+//! ascending order, each the chunk's little-endian `i32` x and y and its
+//! `CHUNK_SIZE²` bits. Unexplored chunks cost nothing, which keeps a typical save to a
+//! few hundred bytes however far the player wanders.
+
+use std::{collections::BTreeMap, fmt};
+
+use super::{pathfinding::Cell, CHUNK_SIZE};
+
+/// First byte of every encoded set; bumped when the layout changes.
+pub const FORMAT_VERSION: u8 = 2;
+
+const WORDS_PER_CHUNK: usize = CHUNK_SIZE * CHUNK_SIZE / 64;
+const CHUNK_BYTES: usize = WORDS_PER_CHUNK * 8;
+const ENTRY_BYTES: usize = 8 + CHUNK_BYTES;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Explored {
+    /// Bits of every chunk with anything explored in it, by chunk coordinates.
+    chunks: BTreeMap<Cell, [u64; WORDS_PER_CHUNK]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExploredError {
+    Empty,
+    UnsupportedVersion(u8),
+    Truncated { offset: usize },
+    ChunksOutOfOrder(Cell),
+}
+
+impl fmt::Display for ExploredError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExploredError::Empty => write!(f, "explored tiles are empty, expected a version byte"),
+            ExploredError::UnsupportedVersion(version) => write!(
+                f,
+                "explored tiles use format {version}, this game reads format {FORMAT_VERSION}"
+            ),
+            ExploredError::Truncated { offset } => {
+                write!(
+                    f,
+                    "explored tiles end in the middle of the chunk at byte {offset}"
+                )
+            }
+            ExploredError::ChunksOutOfOrder((x, y)) => {
+                write!(f, "explored chunk {x}, {y} is repeated or out of order")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExploredError {}
+
+/// Chunk coordinates and bit of `cell`.
+fn locate((x, y): Cell) -> (Cell, usize) {
+    let size = CHUNK_SIZE as i32;
+    let chunk = (x.div_euclid(size), y.div_euclid(size));
+    let bit = y.rem_euclid(size) * size + x.rem_euclid(size);
+    (chunk, bit as usize)
+}
+
+/// Whether `cell` is within `radius` tiles of `centre`. The circle is widened by half
+/// a tile so its edges are not single tiles poking out.
+pub fn in_sight(centre: Cell, radius: i32, cell: Cell) -> bool {
+    let (dx, dy) = (cell.0 - centre.0, cell.1 - centre.1);
+    dx * dx + dy * dy <= radius * radius + radius
+}
+
+impl Explored {
+    pub fn is_explored(&self, cell: Cell) -> bool {
+        let (chunk, bit) = locate(cell);
+        self.chunks
+            .get(&chunk)
+            .is_some_and(|words| words[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Marks every tile in sight of `centre` as explored. Returns whether any of them
+    /// was not explored before.
+    pub fn reveal(&mut self, centre: Cell, radius: i32) -> bool {
+        let mut changed = false;
+        for y in centre.1 - radius..=centre.1 + radius {
+            for x in centre.0 - radius..=centre.0 + radius {
+                if !in_sight(centre, radius, (x, y)) {
+                    continue;
+                }
+                let (chunk, bit) = locate((x, y));
+                let word = &mut self.chunks.entry(chunk).or_default()[bit / 64];
+                changed |= *word & (1 << (bit % 64)) == 0;
+                *word |= 1 << (bit % 64);
+            }
+        }
+        changed
+    }
+
+    /// Adds the tiles explored in `other`.
+    pub fn extend(&mut self, other: &Explored) {
+        for (chunk, others) in &other.chunks {
+            let words = self.chunks.entry(*chunk).or_default();
+            for (word, other) in words.iter_mut().zip(others) {
+                *word |= other;
+            }
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![FORMAT_VERSION];
+        for ((x, y), words) in &self.chunks {
+            bytes.extend(x.to_le_bytes());
+            bytes.extend(y.to_le_bytes());
+            for word in words {
+                bytes.extend(word.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ExploredError> {
+        let (&version, mut rest) = bytes.split_first().ok_or(ExploredError::Empty)?;
+        if version != FORMAT_VERSION {
+            return Err(ExploredError::UnsupportedVersion(version));
+        }
+
+        let mut explored = Explored::default();
+        let mut previous = None;
+        while !rest.is_empty() {
+            let offset = bytes.len() - rest.len();
+            let Some((entry, next)) = rest.split_first_chunk::<ENTRY_BYTES>() else {
+                return Err(ExploredError::Truncated { offset });
+            };
+            let coordinate =
+                |at: usize| i32::from_le_bytes(entry[at..at + 4].try_into().expect("four bytes"));
+            let chunk = (coordinate(0), coordinate(4));
+            if previous.is_some_and(|previous| chunk <= previous) {
+                return Err(ExploredError::ChunksOutOfOrder(chunk));
+            }
+            previous = Some(chunk);
+
+            let mut words = [0; WORDS_PER_CHUNK];
+            for (word, bytes) in words.iter_mut().zip(entry[8..].chunks_exact(8)) {
+                *word = u64::from_le_bytes(bytes.try_into().expect("chunks of eight bytes"));
+            }
+            explored.chunks.insert(chunk, words);
+            rest = next;
+        }
+        Ok(explored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reveal_marks_a_circle_once() {
+        let mut explored = Explored::default();
+        assert!(explored.reveal((3, -2), 4));
+        assert!(!explored.reveal((3, -2), 4), "nothing new the second time");
+
+        assert!(explored.is_explored((3, -2)));
+        assert!(explored.is_explored((7, -2)));
+        assert!(!explored.is_explored((8, -2)));
+        assert!(!explored.is_explored((7, 2)), "corners stay hidden");
+    }
+
+    #[test]
+    fn reveal_spans_chunks_anywhere_in_the_world() {
+        let mut explored = Explored::default();
+        for centre in [(0, 0), (-1_000_000, 2_000_000)] {
+            assert!(explored.reveal(centre, 5));
+            for cell in [(-4, 0), (4, 0), (0, -4), (0, 4)] {
+                assert!(explored.is_explored((centre.0 + cell.0, centre.1 + cell.1)));
+            }
+        }
+        assert!(!explored.is_explored((-1_000_000, 0)));
+    }
+
+    #[test]
+    fn bytes_round_trip_compactly() {
+        let mut explored = Explored::default();
+        assert_eq!(explored.to_bytes(), [FORMAT_VERSION]);
+
+        for step in 0..40 {
+            explored.reveal((step - 20, step / 3), 6);
+        }
+        explored.reveal((-70_000, 90_000), 2);
+        let bytes = explored.to_bytes();
+
+        assert_eq!(Explored::from_bytes(&bytes).as_ref(), Ok(&explored));
+        assert!(bytes.len() < 1024, "{} bytes for a short walk", bytes.len());
+    }
+
+    #[test]
+    fn imports_add_to_what_is_explored() {
+        let mut here = Explored::default();
+        here.reveal((0, 0), 2);
+        let mut there = Explored::default();
+        there.reveal((40, 40), 2);
+
+        here.extend(&there);
+        assert!(here.is_explored((0, 0)));
+        assert!(here.is_explored((40, 40)));
+        assert!(!here.is_explored((20, 20)));
+    }
+
+    #[test]
+    fn malformed_bytes_are_rejected() {
+        let mut explored = Explored::default();
+        explored.reveal((0, 0), 3);
+        explored.reveal((100, 100), 3);
+        let bytes = explored.to_bytes();
+
+        assert_eq!(Explored::from_bytes(&[]), Err(ExploredError::Empty));
+        assert_eq!(
+            Explored::from_bytes(&[7]),
+            Err(ExploredError::UnsupportedVersion(7))
+        );
+        assert_eq!(
+            Explored::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ExploredError::Truncated {
+                offset: 1 + ENTRY_BYTES * 4
+            })
+        );
+
+        let mut repeated = bytes.clone();
+        repeated.extend_from_slice(&bytes[1..1 + ENTRY_BYTES]);
+        assert!(matches!(
+            Explored::from_bytes(&repeated),
+            Err(ExploredError::ChunksOutOfOrder(_))
+        ));
+    }
+}
@@ -0,0 +1,276 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{AppState, Game, Root, Seed};
+
+pub mod autotile;
+pub mod biome;
+pub mod chunk;
+pub mod explored;
+pub mod palette;
+pub mod pathfinding;
+pub mod spawn;
+pub mod terrain;
+
+use biome::{Biome, BiomeRules, Climate};
+use chunk::{spawn_chunk, TileAssets, TileChunk, TileMaterials};
+use palette::{PaletteHandle, TerrainPalette, TerrainPaletteLoader, TileStyle};
+use terrain::TerrainGenerator;
+
+/// World-space edge length of a single map tile.
+pub const TILE_SIZE: f32 = 32.0;
+
+/// Number of tiles along each edge of a render chunk.
+pub const CHUNK_SIZE: usize = 16;
+
+/// Chunks kept around the player even when the camera looks elsewhere.
+const PLAYER_CHUNK_RADIUS: i32 = 2;
+
+/// Extra ring of chunks loaded past the edge of the view.
+const LOAD_MARGIN: i32 = 1;
+
+/// Chunks are only despawned once they are this far past the load area, so panning
+/// back and forth across a border does not rebuild the same meshes.
+const UNLOAD_MARGIN: i32 = 2;
+
+/// Upper bound on chunk meshes built in a single frame.
+const MAX_CHUNKS_PER_FRAME: usize = 8;
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<TerrainPalette>()
+            .init_asset_loader::<TerrainPaletteLoader>()
+            .init_resource::<PaletteHandle>()
+            .init_resource::<TileMaterials>()
+            .add_systems(Update, reload_palette)
+            .add_systems(Update, stream_chunks.run_if(in_state(AppState::Finished)));
+    }
+}
+
+/// Terrain generator for the current map seed.
+#[derive(Resource, Deref)]
+pub struct WorldNoise(TerrainGenerator);
+
+impl WorldNoise {
+    pub fn new(seed: u32, rules: BiomeRules) -> Self {
+        WorldNoise(TerrainGenerator::with_rules(seed, rules))
+    }
+}
+
+/// Terrain lookups by tile, for systems that need the map rather than its meshes.
+#[derive(SystemParam)]
+pub struct Terrain<'w> {
+    noise: Option<Res<'w, WorldNoise>>,
+    palettes: Res<'w, Assets<TerrainPalette>>,
+    palette: Res<'w, PaletteHandle>,
+}
+
+impl Terrain<'_> {
+    /// Biome at `cell`, or `None` before the map exists.
+    pub fn biome(&self, cell: IVec2) -> Option<Biome> {
+        Some(self.noise.as_ref()?.biome(cell.x, cell.y))
+    }
+
+    /// Noise values the biome at `cell` was picked from.
+    pub fn climate(&self, cell: IVec2) -> Option<Climate> {
+        Some(self.noise.as_ref()?.climate(cell.x, cell.y))
+    }
+
+    fn palette(&self) -> &TerrainPalette {
+        self.palette.get(&self.palettes)
+    }
+
+    pub fn style(&self, cell: IVec2) -> Option<&TileStyle> {
+        let biome = self.biome(cell)?;
+        Some(self.palette().style(biome))
+    }
+
+    /// Half the width of the square the player and camera are kept in, if the palette
+    /// gives the world an edge.
+    pub fn world_radius(&self) -> Option<i32> {
+        self.palette().world_radius
+    }
+
+    pub fn is_walkable(&self, cell: IVec2) -> bool {
+        self.movement_cost(cell).is_some()
+    }
+
+    /// Cost of stepping onto `cell`, or `None` if it cannot be entered.
+    pub fn movement_cost(&self, cell: IVec2) -> Option<u32> {
+        if !self.palette().in_bounds(cell) {
+            return None;
+        }
+        self.style(cell)
+            .filter(|style| style.walkable)
+            .map(|style| style.movement_cost)
+    }
+}
+
+/// World position of the centre of `cell`.
+pub fn cell_to_world(cell: IVec2) -> Vec2 {
+    cell.as_vec2() * TILE_SIZE
+}
+
+/// World position of the bottom left corner of `cell`'s square. Tiles are centred on
+/// their cell, so the square starts half a tile before `cell_to_world`.
+pub fn cell_corner(cell: IVec2) -> Vec2 {
+    cell_to_world(cell) - TILE_SIZE / 2.0
+}
+
+/// Tile whose square contains the given world position.
+pub fn world_to_cell(position: Vec2) -> IVec2 {
+    (position / TILE_SIZE).round().as_ivec2()
+}
+
+/// Chunk containing `cell`.
+pub fn cell_to_chunk(cell: IVec2) -> IVec2 {
+    cell.div_euclid(IVec2::splat(CHUNK_SIZE as i32))
+}
+
+/// Inclusive chunk range covering `area`, grown by `margin` chunks on every side.
+fn chunk_range(area: Rect, margin: i32) -> (IVec2, IVec2) {
+    (
+        cell_to_chunk(world_to_cell(area.min)) - margin,
+        cell_to_chunk(world_to_cell(area.max)) + margin,
+    )
+}
+
+fn in_range(coord: IVec2, (min, max): (IVec2, IVec2)) -> bool {
+    coord.cmpge(min).all() && coord.cmple(max).all()
+}
+
+/// Spawns chunks around the camera view and the player, and despawns the ones left
+/// far behind.
+#[allow(clippy::too_many_arguments)]
+fn stream_chunks(
+    mut commands: Commands,
+    root: Res<Root>,
+    noise: Res<WorldNoise>,
+    game: Res<Game>,
+    cameras: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+    transforms: Query<&Transform>,
+    chunks: Query<(Entity, &TileChunk)>,
+    palettes: Res<Assets<TerrainPalette>>,
+    palette: Res<PaletteHandle>,
+    mut tile_assets: TileAssets,
+) {
+    let palette = palette.get(&palettes);
+    if commands.get_entity(**root).is_none() {
+        return;
+    }
+
+    let mut areas = Vec::new();
+    for (transform, projection) in cameras.iter() {
+        let area = projection.area;
+        let offset = transform.translation.truncate();
+        areas.push(Rect::from_corners(area.min + offset, area.max + offset));
+    }
+    if let Some(player) = game.player.entity.and_then(|e| transforms.get(e).ok()) {
+        let centre = player.translation.truncate();
+        let reach = PLAYER_CHUNK_RADIUS as f32 * CHUNK_SIZE as f32 * TILE_SIZE;
+        areas.push(Rect::from_center_half_size(centre, Vec2::splat(reach)));
+    }
+
+    let load: Vec<_> = areas.iter().map(|a| chunk_range(*a, LOAD_MARGIN)).collect();
+    let keep: Vec<_> = areas
+        .iter()
+        .map(|a| chunk_range(*a, UNLOAD_MARGIN))
+        .collect();
+
+    let mut loaded = bevy::utils::HashSet::new();
+    for (entity, chunk) in chunks.iter() {
+        if keep.iter().any(|range| in_range(chunk.coord, *range)) {
+            loaded.insert(chunk.coord);
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    let mut missing = Vec::new();
+    for (min, max) in &load {
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                let coord = IVec2::new(x, y);
+                if loaded.insert(coord) {
+                    missing.push(coord);
+                }
+            }
+        }
+    }
+
+    // Build the chunks nearest to the view first.
+    let focus = areas
+        .first()
+        .map(|a| cell_to_chunk(world_to_cell(a.center())))
+        .unwrap_or_default();
+    missing.sort_by_key(|coord| (*coord - focus).length_squared());
+
+    commands.entity(**root).with_children(|parent| {
+        for coord in missing.into_iter().take(MAX_CHUNKS_PER_FRAME) {
+            let first = coord * CHUNK_SIZE as i32;
+            // One extra ring of tiles so transitions line up across chunk borders.
+            let grid = noise.grid((first - 1).into(), CHUNK_SIZE + 2, CHUNK_SIZE + 2);
+
+            let tiles: Vec<_> = grid
+                .iter()
+                .filter_map(|((x, y), _)| {
+                    let local = IVec2::new(x, y) - first;
+                    if local.min_element() < 0 || local.max_element() >= CHUNK_SIZE as i32 {
+                        return None;
+                    }
+                    let variant = autotile::variant(&grid, x, y)?;
+                    Some((local.as_vec2() * TILE_SIZE, palette.layers(variant)))
+                })
+                .collect();
+
+            let origin = first.as_vec2() * TILE_SIZE;
+            spawn_chunk(parent, coord, origin.extend(0.), &tiles, &mut tile_assets);
+        }
+    });
+}
+
+/// Applies edits to the terrain palette: rebuilds the terrain from the new biome rules
+/// and lets `stream_chunks` redraw every chunk with the new textures.
+#[allow(clippy::too_many_arguments)]
+fn reload_palette(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<TerrainPalette>>,
+    palettes: Res<Assets<TerrainPalette>>,
+    palette: Res<PaletteHandle>,
+    seed: Option<Res<Seed>>,
+    noise: Option<ResMut<WorldNoise>>,
+    chunks: Query<Entity, With<TileChunk>>,
+    mut materials: ResMut<TileMaterials>,
+) {
+    let (mut modified, mut loaded) = (false, false);
+    for event in events.read() {
+        modified |= event.is_modified(&palette.handle);
+        loaded |= event.is_loaded_with_dependencies(&palette.handle);
+    }
+    if !modified && !loaded {
+        return;
+    }
+    let Some(palette) = palettes.get(&palette.handle) else {
+        return;
+    };
+
+    // `generate_world` uses the built-in rules while the palette is still loading; the
+    // palette finishing with the same rules is no change.
+    let mut rules_changed = false;
+    if let (Some(seed), Some(mut noise)) = (seed, noise) {
+        if noise.rules() != &palette.biomes {
+            *noise = WorldNoise::new(seed.0, palette.biomes.clone());
+            warn!("Terrain palette changed; the map may no longer match other clients");
+            rules_changed = true;
+        }
+    }
+    if !modified && !rules_changed {
+        return;
+    }
+
+    materials.clear();
+    for entity in chunks.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
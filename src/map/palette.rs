@@ -0,0 +1,220 @@
+use std::{collections::HashMap, fmt};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use super::{
+    autotile::TileVariant,
+    biome::{Biome, BiomeRules, DEFAULT_PALETTE},
+};
+
+pub const PALETTE_PATH: &str = "terrain.palette.ron";
+
+/// How tiles of one biome look and how units move over them.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TileStyle {
+    pub texture: String,
+    /// Hex colour used where textures are not drawn, e.g. debug views.
+    pub color: String,
+    pub walkable: bool,
+    /// Relative cost of stepping onto the tile; ignored when not walkable.
+    pub movement_cost: u32,
+}
+
+impl TileStyle {
+    pub fn color(&self) -> Color {
+        // Checked when the palette is loaded.
+        Color::hex(&self.color).unwrap_or(Color::FUCHSIA)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Transitions {
+    /// Beach along the north edge of a tile, drawn over grass and turned to face the
+    /// water.
+    pub shore_edge: String,
+    /// Beach in the north east corner, for water that only touches a corner.
+    pub shore_corner: String,
+    /// Thinned forest tiles, sparsest first.
+    pub forest_edges: Vec<String>,
+}
+
+/// Designer-tunable terrain data loaded from `assets/terrain.palette.ron`.
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
+pub struct TerrainPalette {
+    pub biomes: BiomeRules,
+    pub tiles: HashMap<Biome, TileStyle>,
+    pub transitions: Transitions,
+    /// Keeps the player and the camera within `-world_radius..world_radius` tiles on
+    /// both axes. Left out, the world is open in every direction.
+    #[serde(default)]
+    pub world_radius: Option<i32>,
+}
+
+impl TerrainPalette {
+    /// The copy of `assets/terrain.palette.ron` compiled into the game.
+    pub fn builtin() -> Self {
+        ron::from_str(DEFAULT_PALETTE).expect("assets/terrain.palette.ron is valid")
+    }
+
+    /// Whether `cell` is inside `world_radius`, if the palette sets one.
+    pub fn in_bounds(&self, cell: IVec2) -> bool {
+        self.world_radius.is_none_or(|radius| {
+            cell.cmpge(IVec2::splat(-radius)).all() && cell.cmplt(IVec2::splat(radius)).all()
+        })
+    }
+
+    pub fn style(&self, biome: Biome) -> &TileStyle {
+        &self.tiles[&biome]
+    }
+
+    /// Textures drawn for `variant`, bottom first, each with the quarter turns clockwise
+    /// it is rotated by.
+    pub fn layers(&self, variant: TileVariant) -> Vec<(&str, u8)> {
+        match variant {
+            TileVariant::Base(biome) => vec![(&self.style(biome).texture, 0)],
+            TileVariant::Shore(sides) => {
+                let transitions = &self.transitions;
+                let edges = sides
+                    .edges()
+                    .map(|turns| (transitions.shore_edge.as_str(), turns));
+                let corners = sides
+                    .corners()
+                    .map(|turns| (transitions.shore_corner.as_str(), turns));
+                [(self.style(Biome::Grassland).texture.as_str(), 0)]
+                    .into_iter()
+                    .chain(edges)
+                    .chain(corners)
+                    .collect()
+            }
+            TileVariant::ForestEdge(density) => {
+                let edges = &self.transitions.forest_edges;
+                vec![(&edges[usize::from(density).min(edges.len() - 1)], 0)]
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for biome in [
+            Biome::Ocean,
+            Biome::Lake,
+            Biome::Sand,
+            Biome::Grassland,
+            Biome::Forest,
+            Biome::Tundra,
+        ] {
+            let style = self
+                .tiles
+                .get(&biome)
+                .ok_or_else(|| format!("no tile style for {biome:?}"))?;
+            Color::hex(&style.color)
+                .map_err(|_| format!("invalid colour {:?} for {biome:?}", style.color))?;
+        }
+        if let Some(radius) = self.world_radius.filter(|radius| *radius <= 0) {
+            return Err(format!("world radius {radius} is not positive"));
+        }
+        if self.transitions.forest_edges.is_empty() {
+            return Err("no forest edge textures".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+pub struct PaletteHandle {
+    pub handle: Handle<TerrainPalette>,
+    builtin: TerrainPalette,
+}
+
+impl PaletteHandle {
+    /// The loaded palette, or the built-in one while the asset is loading or if it is
+    /// missing or invalid, so the map never waits on it.
+    pub fn get<'a>(&'a self, palettes: &'a Assets<TerrainPalette>) -> &'a TerrainPalette {
+        palettes.get(&self.handle).unwrap_or(&self.builtin)
+    }
+}
+
+impl FromWorld for PaletteHandle {
+    fn from_world(world: &mut World) -> Self {
+        PaletteHandle {
+            handle: world.resource::<AssetServer>().load(PALETTE_PATH),
+            builtin: TerrainPalette::builtin(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PaletteLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl fmt::Display for PaletteLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaletteLoaderError::Io(e) => write!(f, "could not read terrain palette: {e}"),
+            PaletteLoaderError::Ron(e) => write!(f, "could not parse terrain palette: {e}"),
+            PaletteLoaderError::Invalid(e) => write!(f, "invalid terrain palette: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PaletteLoaderError {}
+
+#[derive(Default)]
+pub struct TerrainPaletteLoader;
+
+impl AssetLoader for TerrainPaletteLoader {
+    type Asset = TerrainPalette;
+    type Settings = ();
+    type Error = PaletteLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(PaletteLoaderError::Io)?;
+            let palette: TerrainPalette =
+                ron::de::from_bytes(&bytes).map_err(PaletteLoaderError::Ron)?;
+            palette.validate().map_err(PaletteLoaderError::Invalid)?;
+            Ok(palette)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["palette.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_palette_is_valid() {
+        assert_eq!(TerrainPalette::builtin().validate(), Ok(()));
+    }
+
+    #[test]
+    fn world_radius_bounds_cells_only_when_set() {
+        let mut palette = TerrainPalette::builtin();
+        assert!(palette.in_bounds(IVec2::new(1_000_000, -1_000_000)));
+
+        palette.world_radius = Some(4);
+        assert!(palette.in_bounds(IVec2::new(-4, 3)));
+        assert!(!palette.in_bounds(IVec2::new(4, 0)));
+        assert!(!palette.in_bounds(IVec2::new(0, -5)));
+    }
+}
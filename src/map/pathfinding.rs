@@ -0,0 +1,171 @@
+//! A* search over the tile grid.
+//!
+//! Engine-free like `terrain`: callers supply the cost of entering each tile, so the
+//! same search works against the live map, a `TerrainGrid` or test data.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+pub type Cell = (i32, i32);
+
+/// Cost of a straight step onto a tile with movement cost `1`; diagonals cost `14`.
+const STRAIGHT: u32 = 10;
+const DIAGONAL: u32 = 14;
+
+const STEPS: [(i32, i32); 8] = [
+    (0, 1),
+    (1, 0),
+    (0, -1),
+    (-1, 0),
+    (1, 1),
+    (1, -1),
+    (-1, -1),
+    (-1, 1),
+];
+
+/// Lower bound on the cost from `a` to `b`, assuming every tile costs `1`.
+fn octile(a: Cell, b: Cell) -> u32 {
+    let dx = a.0.abs_diff(b.0);
+    let dy = a.1.abs_diff(b.1);
+    STRAIGHT * dx.max(dy) + (DIAGONAL - STRAIGHT) * dx.min(dy)
+}
+
+/// Cheapest 8-connected path from `start` to `goal`, including both ends.
+///
+/// `cost` returns the movement cost of entering a tile, or `None` if it cannot be
+/// entered; costs below `1` are treated as `1`. Diagonal steps may not cut the corner
+/// of a blocked tile. The search gives up after expanding `max_nodes` tiles, so an
+/// unreachable goal on an unbounded map does not stall the frame.
+pub fn find_path(
+    start: Cell,
+    goal: Cell,
+    cost: impl Fn(Cell) -> Option<u32>,
+    max_nodes: usize,
+) -> Option<Vec<Cell>> {
+    if start == goal {
+        return Some(vec![start]);
+    }
+    cost(goal)?;
+
+    let mut open = BinaryHeap::new();
+    let mut best: HashMap<Cell, u32> = HashMap::new();
+    let mut came_from: HashMap<Cell, Cell> = HashMap::new();
+
+    best.insert(start, 0);
+    open.push(Reverse((octile(start, goal), 0, start)));
+
+    let mut expanded = 0;
+    while let Some(Reverse((_, spent, cell))) = open.pop() {
+        if cell == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(previous) = came_from.get(&current) {
+                path.push(*previous);
+                current = *previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        // Skip stale heap entries for tiles already reached more cheaply.
+        if best.get(&cell).is_some_and(|b| *b < spent) {
+            continue;
+        }
+
+        expanded += 1;
+        if expanded > max_nodes {
+            return None;
+        }
+
+        for (dx, dy) in STEPS {
+            let next = (cell.0 + dx, cell.1 + dy);
+            let Some(tile_cost) = cost(next) else {
+                continue;
+            };
+
+            let diagonal = dx != 0 && dy != 0;
+            if diagonal
+                && (cost((cell.0 + dx, cell.1)).is_none() || cost((cell.0, cell.1 + dy)).is_none())
+            {
+                continue;
+            }
+
+            let step = if diagonal { DIAGONAL } else { STRAIGHT };
+            let total = spent + step * tile_cost.max(1);
+            if best.get(&next).is_some_and(|b| *b <= total) {
+                continue;
+            }
+
+            best.insert(next, total);
+            came_from.insert(next, cell);
+            open.push(Reverse((total + octile(next, goal), total, next)));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Movement costs from rows of tiles, top row first, with its bottom left tile at
+    /// `(0, 0)`: `.` costs 1, a digit its value and `#` or anything off the map blocks.
+    fn map(rows: &'static [&'static str]) -> impl Fn(Cell) -> Option<u32> {
+        move |(x, y)| {
+            let row = rows
+                .len()
+                .checked_sub(1)?
+                .checked_sub(usize::try_from(y).ok()?)?;
+            let tile = rows[row].as_bytes().get(usize::try_from(x).ok()?)?;
+            match tile {
+                b'.' => Some(1),
+                b'#' => None,
+                digit => Some(u32::from(digit - b'0')),
+            }
+        }
+    }
+
+    #[test]
+    fn walks_straight_lines() {
+        let path = find_path((0, 0), (4, 0), map(&["....."]), 100);
+        assert_eq!(path, Some(vec![(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)]));
+        assert_eq!(
+            find_path((2, 0), (2, 0), map(&["....."]), 100),
+            Some(vec![(2, 0)])
+        );
+    }
+
+    #[test]
+    fn does_not_cut_blocked_corners() {
+        let path = find_path((0, 0), (1, 1), map(&["..", ".#"]), 100);
+        assert_eq!(path, Some(vec![(0, 0), (0, 1), (1, 1)]));
+
+        assert_eq!(find_path((0, 0), (1, 1), map(&["#.", ".#"]), 100), None);
+    }
+
+    #[test]
+    fn prefers_cheap_tiles_over_short_routes() {
+        let costs = map(&[".....", ".999.", "....."]);
+        let path = find_path((0, 1), (4, 1), &costs, 100).expect("a path");
+        assert_eq!(path.first(), Some(&(0, 1)));
+        assert_eq!(path.last(), Some(&(4, 1)));
+        assert!(path.iter().all(|cell| costs(*cell) == Some(1)), "{path:?}");
+    }
+
+    #[test]
+    fn unreachable_goals_have_no_path() {
+        let walled = map(&["..#.", "..#.", "..#."]);
+        assert_eq!(find_path((0, 0), (3, 0), &walled, 100), None);
+        assert_eq!(find_path((0, 0), (2, 0), &walled, 100), None);
+    }
+
+    #[test]
+    fn gives_up_after_max_nodes() {
+        let open = |_| Some(1);
+        assert_eq!(find_path((0, 0), (50, 0), open, 10), None);
+        let path = find_path((0, 0), (50, 0), open, 1000).expect("a path");
+        assert_eq!(path.len(), 51);
+    }
+}
@@ -0,0 +1,226 @@
+//! Deterministic spawn tiles for registered players.
+//!
+//! Every client derives the same tiles from the map seed and the registry alone, so
+//! units line up across clients without the chain storing positions. Addresses are
+//! placed in registry order and keep `MIN_SPACING` from the ones before them, so a
+//! player joining never moves anyone registered earlier.
+
+use std::collections::{BTreeMap, HashSet};
+
+use super::pathfinding::Cell;
+
+/// Spawns are hashed into the square `-SPAWN_RADIUS..SPAWN_RADIUS` around the origin.
+pub const SPAWN_RADIUS: i32 = 48;
+
+/// How far from its hashed tile a spawn may move looking for walkable land.
+pub const MAX_SEARCH_RADIUS: i32 = 32;
+
+/// Chebyshev distance kept between spawns while the map has room for it.
+pub const MIN_SPACING: i32 = 6;
+
+/// FNV-1a over the seed and the address; stable across platforms and releases.
+pub fn address_hash(seed: u32, address: &str) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    seed.to_le_bytes()
+        .iter()
+        .chain(address.as_bytes())
+        .fold(OFFSET, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+        })
+}
+
+/// Tile the address hashes to, before looking for land.
+fn hashed_cell(hash: u64) -> Cell {
+    let span = (2 * SPAWN_RADIUS) as u64;
+    (
+        (hash % span) as i32 - SPAWN_RADIUS,
+        (hash / span % span) as i32 - SPAWN_RADIUS,
+    )
+}
+
+fn distance(a: Cell, b: Cell) -> i32 {
+    (a.0 - b.0).abs().max((a.1 - b.1).abs())
+}
+
+/// Spawn tile of every address, given in registry order. Each address takes the
+/// walkable tile closest to where it hashes to that keeps `MIN_SPACING` from earlier
+/// spawns, or the closest free walkable tile when it is too crowded for that.
+/// Addresses without land within `MAX_SEARCH_RADIUS` are left out; repeated
+/// addresses keep their first place.
+pub fn spawn_cells<'a>(
+    seed: u32,
+    addresses: impl IntoIterator<Item = &'a str>,
+    is_walkable: impl Fn(Cell) -> bool,
+) -> BTreeMap<&'a str, Cell> {
+    let mut spawns = BTreeMap::new();
+    let mut taken: Vec<Cell> = Vec::new();
+    let mut seen = HashSet::new();
+
+    for address in addresses {
+        if !seen.insert(address) {
+            continue;
+        }
+        let start = hashed_cell(address_hash(seed, address));
+        let land = || search(start).filter(|cell| is_walkable(*cell));
+
+        let spaced = land().find(|cell| taken.iter().all(|t| distance(*t, *cell) >= MIN_SPACING));
+        let Some(cell) = spaced.or_else(|| land().find(|cell| !taken.contains(cell))) else {
+            continue;
+        };
+        taken.push(cell);
+        spawns.insert(address, cell);
+    }
+    spawns
+}
+
+/// Walkable tile closest to `start`, for players without an address.
+pub fn nearest_walkable(start: Cell, is_walkable: impl Fn(Cell) -> bool) -> Option<Cell> {
+    search(start).find(|cell| is_walkable(*cell))
+}
+
+/// Tiles within `MAX_SEARCH_RADIUS` of `start`, ring by ring.
+fn search(start: Cell) -> impl Iterator<Item = Cell> {
+    (0..=MAX_SEARCH_RADIUS).flat_map(move |radius| ring(start, radius))
+}
+
+/// Tiles at Chebyshev distance `radius` from `centre`, in a fixed order.
+fn ring(centre: Cell, radius: i32) -> impl Iterator<Item = Cell> {
+    let (cx, cy) = centre;
+    (-radius..=radius).flat_map(move |dy| {
+        let edge = dy.abs() == radius;
+        let step = if edge || radius == 0 { 1 } else { 2 * radius };
+        (-radius..=radius)
+            .step_by(step as usize)
+            .map(move |dx| (cx + dx, cy + dy))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::{biome::Biome, terrain::TerrainGenerator};
+
+    const SEEDS: u32 = 40;
+    const PLAYERS: usize = 24;
+
+    fn addresses() -> Vec<String> {
+        (0..PLAYERS)
+            .map(|i| {
+                format!(
+                    "0x{:064x}",
+                    (i as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+                )
+            })
+            .collect()
+    }
+
+    /// Walkability of the default palette.
+    fn walkable(terrain: &TerrainGenerator) -> impl Fn(Cell) -> bool + '_ {
+        |(x, y)| {
+            matches!(
+                terrain.biome(x, y),
+                Biome::Sand | Biome::Grassland | Biome::Tundra
+            )
+        }
+    }
+
+    #[test]
+    fn spawns_are_reproducible_and_on_land() {
+        let addresses = addresses();
+        for seed in 0..SEEDS {
+            let terrain = TerrainGenerator::new(seed);
+            let first = spawn_cells(
+                seed,
+                addresses.iter().map(String::as_str),
+                walkable(&terrain),
+            );
+            let again = spawn_cells(
+                seed,
+                addresses.iter().map(String::as_str),
+                walkable(&terrain),
+            );
+
+            assert_eq!(first, again, "seed {seed}");
+            assert_eq!(
+                first.len(),
+                PLAYERS,
+                "seed {seed} left players without land"
+            );
+            for (address, cell) in &first {
+                assert!(
+                    walkable(&terrain)(*cell),
+                    "seed {seed}: {address} spawns in water"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn spawns_keep_their_distance() {
+        let addresses = addresses();
+        for seed in 0..SEEDS {
+            let terrain = TerrainGenerator::new(seed);
+            let spawns = spawn_cells(
+                seed,
+                addresses.iter().map(String::as_str),
+                walkable(&terrain),
+            );
+            let cells: Vec<Cell> = spawns.values().copied().collect();
+
+            for (i, a) in cells.iter().enumerate() {
+                for b in &cells[i + 1..] {
+                    assert!(
+                        distance(*a, *b) >= MIN_SPACING,
+                        "seed {seed}: spawns {a:?} and {b:?} are clustered"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn joining_players_do_not_move_earlier_ones() {
+        let addresses = addresses();
+        for seed in 0..SEEDS {
+            let terrain = TerrainGenerator::new(seed);
+            let before = spawn_cells(
+                seed,
+                addresses[..PLAYERS / 2].iter().map(String::as_str),
+                walkable(&terrain),
+            );
+            let after = spawn_cells(
+                seed,
+                addresses.iter().map(String::as_str),
+                walkable(&terrain),
+            );
+
+            for (address, cell) in &before {
+                assert_eq!(after[address], *cell, "seed {seed}: {address} moved");
+            }
+        }
+    }
+
+    #[test]
+    fn seeds_and_addresses_spread_spawns() {
+        let terrain = TerrainGenerator::new(7);
+        let a = spawn_cells(7, ["0xa"], walkable(&terrain));
+        let b = spawn_cells(8, ["0xa"], walkable(&TerrainGenerator::new(8)));
+        let c = spawn_cells(7, ["0xb"], walkable(&terrain));
+        assert_ne!(a["0xa"], b["0xa"]);
+        assert_ne!(a["0xa"], c["0xb"]);
+    }
+
+    #[test]
+    fn crowded_maps_fall_back_to_free_land() {
+        // A single walkable row only fits a spawn every `MIN_SPACING` tiles.
+        let row = |(_, y): Cell| y == 0;
+        let addresses: Vec<String> = (0..40).map(|i| format!("0x{i:x}")).collect();
+        let spawns = spawn_cells(1, addresses.iter().map(String::as_str), row);
+
+        let cells: HashSet<Cell> = spawns.values().copied().collect();
+        assert_eq!(cells.len(), spawns.len(), "two players share a tile");
+        assert!(cells.iter().all(|cell| row(*cell)));
+    }
+}
@@ -0,0 +1,204 @@
+//! Engine-free terrain generation.
+//!
+//! Every client derives the map from the on-chain seed alone, so this module must give
+//! the same `Biome` for a cell on every platform. It only depends on `noise` (pinned
+//! in `Cargo.toml`), the biome rules in `assets/terrain.palette.ron` and plain `f64` arithmetic, which
+//! is IEEE-754 exact on both native and wasm32 targets. The golden fingerprints in the
+//! tests below must only change together with a deliberate change to those rules.
+
+use super::biome::{Biome, BiomeRules, Climate, ClimateNoise};
+
+/// Seeded climate noise and the rules turning it into biomes.
+pub struct TerrainGenerator {
+    climate: ClimateNoise,
+    rules: BiomeRules,
+}
+
+impl TerrainGenerator {
+    /// Generator with the default biome rules; the game always passes the palette's.
+    #[cfg(test)]
+    pub fn new(seed: u32) -> Self {
+        TerrainGenerator::with_rules(seed, BiomeRules::default())
+    }
+
+    pub fn with_rules(seed: u32, rules: BiomeRules) -> Self {
+        TerrainGenerator {
+            climate: ClimateNoise::new(seed, &rules.layers),
+            rules,
+        }
+    }
+
+    pub fn rules(&self) -> &BiomeRules {
+        &self.rules
+    }
+
+    /// Climate of the tile at `(x, y)`, where `(0, 0)` is centred on the origin.
+    pub fn climate(&self, x: i32, y: i32) -> Climate {
+        self.climate.sample(x, y)
+    }
+
+    pub fn biome(&self, x: i32, y: i32) -> Biome {
+        self.rules.classify(&self.climate(x, y))
+    }
+
+    /// Classifies the `width` x `height` block of tiles starting at `origin`.
+    pub fn grid(&self, origin: (i32, i32), width: usize, height: usize) -> TerrainGrid {
+        let mut tiles = Vec::with_capacity(width * height);
+        for row in 0..height {
+            for col in 0..width {
+                tiles.push(self.biome(origin.0 + col as i32, origin.1 + row as i32));
+            }
+        }
+
+        TerrainGrid {
+            origin,
+            width,
+            height,
+            tiles,
+        }
+    }
+}
+
+/// A rectangular block of classified tiles, stored row by row from `origin`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerrainGrid {
+    origin: (i32, i32),
+    width: usize,
+    height: usize,
+    tiles: Vec<Biome>,
+}
+
+impl TerrainGrid {
+    /// Generates a grid with the default biome rules.
+    #[cfg(test)]
+    pub fn generate(seed: u32, origin: (i32, i32), width: usize, height: usize) -> Self {
+        TerrainGenerator::new(seed).grid(origin, width, height)
+    }
+
+    /// Grid of the given tiles, stored row by row from `origin`.
+    #[cfg(test)]
+    pub fn from_tiles(origin: (i32, i32), width: usize, height: usize, tiles: Vec<Biome>) -> Self {
+        assert_eq!(tiles.len(), width * height);
+        TerrainGrid {
+            origin,
+            width,
+            height,
+            tiles,
+        }
+    }
+
+    /// Every tile in the grid together with its world cell.
+    pub fn iter(&self) -> impl Iterator<Item = ((i32, i32), Biome)> + '_ {
+        self.tiles.iter().enumerate().map(|(i, biome)| {
+            let col = (i % self.width) as i32;
+            let row = (i / self.width) as i32;
+            ((self.origin.0 + col, self.origin.1 + row), *biome)
+        })
+    }
+
+    /// Biome of the tile at world cell `(x, y)`, or `None` outside the grid.
+    pub fn get(&self, x: i32, y: i32) -> Option<Biome> {
+        let col = usize::try_from(x - self.origin.0).ok()?;
+        let row = usize::try_from(y - self.origin.1).ok()?;
+        if col >= self.width || row >= self.height {
+            return None;
+        }
+        Some(self.tiles[row * self.width + col])
+    }
+
+    /// Stable 64-bit FNV-1a hash of the grid's extent and contents.
+    pub fn fingerprint(&self) -> u64 {
+        const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let header = [
+            self.origin.0.to_le_bytes(),
+            self.origin.1.to_le_bytes(),
+            (self.width as u32).to_le_bytes(),
+            (self.height as u32).to_le_bytes(),
+        ];
+
+        header
+            .iter()
+            .flatten()
+            .copied()
+            .chain(self.tiles.iter().map(|biome| *biome as u8))
+            .fold(OFFSET, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(PRIME)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The area covered by the original fixed 100x100 map.
+    fn classic_map(seed: u32) -> TerrainGrid {
+        TerrainGrid::generate(seed, (-50, -50), 100, 100)
+    }
+
+    #[test]
+    fn golden_fingerprints() {
+        let golden: [(u32, u64); 6] = [
+            (1, 0x8ffd_cfdb_4e56_b0ff),
+            (42, 0x4fee_fd29_0b0e_c3c3),
+            (1337, 0x402b_a554_c0b0_76da),
+            (0x5eed_5eed, 0xa4fc_ccd8_e647_9e89),
+            (0xdead_beef, 0x6419_fcb3_9ce5_29bd),
+            (u32::MAX, 0x025f_58f6_bde4_7be8),
+        ];
+
+        for (seed, expected) in golden {
+            assert_eq!(
+                classic_map(seed).fingerprint(),
+                expected,
+                "terrain for seed {seed} changed"
+            );
+        }
+    }
+
+    #[test]
+    fn generation_is_repeatable() {
+        assert_eq!(classic_map(7), classic_map(7));
+    }
+
+    #[test]
+    fn seeds_produce_different_maps() {
+        assert_ne!(classic_map(7).fingerprint(), classic_map(8).fingerprint());
+    }
+
+    #[test]
+    fn default_rules_produce_every_biome() {
+        let grid = TerrainGrid::generate(42, (-200, -200), 400, 400);
+
+        for biome in [
+            Biome::Ocean,
+            Biome::Lake,
+            Biome::Sand,
+            Biome::Grassland,
+            Biome::Forest,
+            Biome::Tundra,
+        ] {
+            assert!(
+                grid.iter().any(|(_, b)| b == biome),
+                "no {biome:?} generated"
+            );
+        }
+    }
+
+    #[test]
+    fn grid_matches_generator() {
+        let grid = TerrainGrid::generate(99, (-3, 5), 4, 6);
+        let generator = TerrainGenerator::new(99);
+
+        let cells: Vec<_> = grid.iter().map(|(cell, _)| cell).collect();
+        assert_eq!(cells.len(), 24);
+        assert_eq!(cells.first(), Some(&(-3, 5)));
+        assert_eq!(cells.last(), Some(&(0, 10)));
+
+        for ((x, y), biome) in grid.iter() {
+            assert_eq!(biome, generator.biome(x, y));
+        }
+    }
+}
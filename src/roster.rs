@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    chain::{
+        bridge::RosterReceived,
+        ledger::{shorten_address, LocalPlayer},
+        victory::normalize_address,
+    },
+    map::{cell_to_world, spawn, Terrain, WorldNoise, TILE_SIZE},
+    travel::Route,
+    Game, Seed, PLAYER_Z,
+};
+
+/// Draw order of other players' units, just below the local player.
+const UNIT_Z: f32 = 0.95;
+
+/// Number of `Unit/medievalUnit_NN.png` sprites to pick from.
+const UNIT_SPRITES: u64 = 23;
+
+pub struct RosterPlugin;
+
+impl Plugin for RosterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Roster>()
+            .add_systems(Update, (receive_roster, place_units).chain());
+    }
+}
+
+/// Addresses in the on-chain `Registry` and the units standing in for them.
+#[derive(Resource, Default)]
+pub struct Roster {
+    addresses: Vec<String>,
+    units: HashMap<String, Entity>,
+    /// Set when the units have to be placed again, e.g. for a new map.
+    stale: bool,
+}
+
+#[derive(Component)]
+pub struct Unit {
+    pub cell: IVec2,
+}
+
+fn receive_roster(
+    mut received: EventReader<RosterReceived>,
+    mut roster: ResMut<Roster>,
+    noise: Option<Res<WorldNoise>>,
+    local_player: Res<LocalPlayer>,
+) {
+    if let Some(RosterReceived(addresses)) = received.read().last() {
+        // Registry order decides who gets the better spot, see `map::spawn`.
+        roster.addresses = addresses.iter().map(|a| normalize_address(a)).collect();
+        roster.stale = true;
+    }
+    if noise.is_some_and(|noise| noise.is_changed()) || local_player.is_changed() {
+        roster.stale = true;
+    }
+}
+
+/// Spawns a unit for every registered address except the local player, on the tile
+/// `map::spawn` derives for it, and despawns units of addresses no longer listed.
+/// The local player is moved to their own spawn tile too, unless they have already
+/// walked away from it.
+#[allow(clippy::too_many_arguments)]
+fn place_units(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut roster: ResMut<Roster>,
+    seed: Option<Res<Seed>>,
+    terrain: Terrain,
+    local_player: Res<LocalPlayer>,
+    mut game: ResMut<Game>,
+    mut route: ResMut<Route>,
+    mut units: Query<(&mut Unit, &mut Transform)>,
+    mut players: Query<(&mut Transform, &mut Visibility), Without<Unit>>,
+) {
+    if !roster.stale {
+        return;
+    }
+    // Spawn tiles depend on the terrain; wait for the map.
+    let Some(seed) = seed else {
+        return;
+    };
+    if terrain.style(IVec2::ZERO).is_none() {
+        return;
+    }
+    let roster = &mut *roster;
+    roster.stale = false;
+
+    // Someone not in the registry yet is placed as if they had just joined.
+    let local = local_player.0.as_deref().map(normalize_address);
+    let addresses = roster
+        .addresses
+        .iter()
+        .map(String::as_str)
+        .chain(local.as_deref());
+    let is_walkable = |cell: (i32, i32)| terrain.is_walkable(cell.into());
+    let spawns = spawn::spawn_cells(seed.0, addresses, is_walkable);
+
+    let player = &mut game.player;
+    if player.spawn.is_none_or(|spawn| spawn == player.cell) {
+        let cell = local
+            .as_deref()
+            .and_then(|address| spawns.get(address).copied())
+            .or_else(|| spawn::nearest_walkable((0, 0), is_walkable))
+            .map_or(IVec2::ZERO, IVec2::from);
+        if let Some((mut transform, mut visibility)) = player
+            .entity
+            .and_then(|entity| players.get_mut(entity).ok())
+        {
+            transform.translation = cell_to_world(cell).extend(PLAYER_Z);
+            *visibility = Visibility::Inherited;
+        }
+        route.cancel();
+        player.cell = cell;
+        player.spawn = Some(cell);
+    }
+
+    let mut previous = std::mem::take(&mut roster.units);
+    for address in &roster.addresses {
+        if local.as_ref() == Some(address) {
+            continue;
+        }
+        let Some(cell) = spawns.get(address.as_str()).copied().map(IVec2::from) else {
+            warn!("No land to spawn {} on", address);
+            continue;
+        };
+        let translation = cell_to_world(cell).extend(UNIT_Z);
+
+        let entity = match previous.remove(address) {
+            Some(entity) => {
+                if let Ok((mut unit, mut transform)) = units.get_mut(entity) {
+                    if unit.cell != cell {
+                        unit.cell = cell;
+                        transform.translation = translation;
+                    }
+                }
+                entity
+            }
+            None => spawn_unit(&mut commands, &asset_server, seed.0, address, cell),
+        };
+        roster.units.insert(address.clone(), entity);
+    }
+
+    for entity in previous.into_values() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn spawn_unit(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    seed: u32,
+    address: &str,
+    cell: IVec2,
+) -> Entity {
+    // Same look on every client, independent of where the unit stands.
+    let sprite = spawn::address_hash(seed, address) >> 40;
+    let texture = format!("Unit/medievalUnit_{:02}.png", sprite % UNIT_SPRITES + 1);
+
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(TILE_SIZE)),
+                    ..default()
+                },
+                texture: asset_server.load(texture),
+                transform: Transform::from_translation(cell_to_world(cell).extend(UNIT_Z)),
+                ..default()
+            },
+            Unit { cell },
+        ))
+        .with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(
+                    shorten_address(address),
+                    TextStyle {
+                        font: asset_server.load("PoetsenOne-Regular.ttf"),
+                        font_size: 12.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                transform: Transform::from_xyz(0.0, TILE_SIZE * 0.7, 0.01),
+                ..default()
+            });
+        })
+        .id()
+}
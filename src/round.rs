@@ -0,0 +1,337 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::chain::{
+    bridge::{ClockReceived, GameEvent, RoundReceived, RoundTime},
+    ledger::{shorten_address, Leaderboards, Victory},
+};
+
+const FONT: &str = "PoetsenOne-Regular.ttf";
+
+/// Where the current round is in its lifetime, as far as the last chain clock knows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, States)]
+pub enum RoundPhase {
+    /// The web app has not scheduled a round; the map is free play.
+    #[default]
+    Unscheduled,
+    /// Scheduled, but not started yet or no chain clock received.
+    Pending,
+    Running,
+    Over,
+}
+
+pub struct RoundPlugin;
+
+impl Plugin for RoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<RoundPhase>()
+            .init_resource::<ChainClock>()
+            .add_systems(Startup, spawn_countdown)
+            .add_systems(
+                Update,
+                (receive_round, update_phase, update_countdown).chain(),
+            )
+            .add_systems(OnEnter(RoundPhase::Over), (announce_end, show_results))
+            .add_systems(
+                Update,
+                show_results
+                    .run_if(in_state(RoundPhase::Over))
+                    .run_if(resource_changed::<Leaderboards>),
+            )
+            .add_systems(OnExit(RoundPhase::Over), hide_results);
+    }
+}
+
+/// Round window in chain time; the round runs from `start` up to, not including, `end`.
+#[derive(Debug, Clone, Copy)]
+enum RoundWindow {
+    Epochs { start: u64, end: u64 },
+    Timestamps { start_ms: u64, end_ms: u64 },
+}
+
+#[derive(Resource, Debug)]
+pub struct Round {
+    pub number: u32,
+    window: RoundWindow,
+}
+
+/// Last chain time pushed by the web app and when it arrived.
+#[derive(Resource, Default)]
+struct ChainClock {
+    epoch: Option<u64>,
+    timestamp_ms: Option<u64>,
+    received_at: Duration,
+}
+
+impl ChainClock {
+    /// Chain timestamp now, counting on locally since the last update.
+    fn now_ms(&self, time: &Time) -> Option<u64> {
+        let since = time.elapsed().saturating_sub(self.received_at);
+        self.timestamp_ms
+            .map(|ms| ms.saturating_add(since.as_millis() as u64))
+    }
+}
+
+/// Time left until the round starts or ends.
+enum Countdown {
+    Epochs(u64),
+    Millis(u64),
+}
+
+impl Round {
+    fn phase(&self, clock: &ChainClock, time: &Time) -> RoundPhase {
+        let (now, start, end) = match self.window {
+            RoundWindow::Epochs { start, end } => (clock.epoch, start, end),
+            RoundWindow::Timestamps { start_ms, end_ms } => (clock.now_ms(time), start_ms, end_ms),
+        };
+        match now {
+            Some(now) if now >= end => RoundPhase::Over,
+            Some(now) if now >= start => RoundPhase::Running,
+            _ => RoundPhase::Pending,
+        }
+    }
+
+    /// Until the start while pending, until the end while running.
+    fn countdown(&self, phase: RoundPhase, clock: &ChainClock, time: &Time) -> Option<Countdown> {
+        let running = phase == RoundPhase::Running;
+        match self.window {
+            RoundWindow::Epochs { start, end } => {
+                let target = if running { end } else { start };
+                Some(Countdown::Epochs(target.saturating_sub(clock.epoch?)))
+            }
+            RoundWindow::Timestamps { start_ms, end_ms } => {
+                let target = if running { end_ms } else { start_ms };
+                Some(Countdown::Millis(
+                    target.saturating_sub(clock.now_ms(time)?),
+                ))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Countdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Countdown::Epochs(1) => write!(f, "1 epoch"),
+            Countdown::Epochs(epochs) => write!(f, "{epochs} epochs"),
+            Countdown::Millis(ms) => {
+                let seconds = ms.div_ceil(1000);
+                let (hours, minutes) = (seconds / 3600, seconds / 60 % 60);
+                if hours >= 24 {
+                    write!(f, "{}d {:02}h", hours / 24, hours % 24)
+                } else {
+                    write!(f, "{:02}:{:02}:{:02}", hours, minutes, seconds % 60)
+                }
+            }
+        }
+    }
+}
+
+fn receive_round(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut rounds: EventReader<RoundReceived>,
+    mut clocks: EventReader<ClockReceived>,
+    mut clock: ResMut<ChainClock>,
+) {
+    if let Some(round) = rounds.read().last() {
+        let window = match (round.start, round.end) {
+            (RoundTime::Epoch(start), RoundTime::Epoch(end)) => RoundWindow::Epochs { start, end },
+            (RoundTime::TimestampMs(start_ms), RoundTime::TimestampMs(end_ms)) => {
+                RoundWindow::Timestamps { start_ms, end_ms }
+            }
+            // Rejected by the bridge already.
+            _ => return,
+        };
+        info!("Round {} scheduled: {:?}", round.round, window);
+        commands.insert_resource(Round {
+            number: round.round,
+            window,
+        });
+    }
+
+    if let Some(update) = clocks.read().last() {
+        clock.epoch = Some(update.epoch);
+        clock.timestamp_ms = Some(update.timestamp_ms);
+        clock.received_at = time.elapsed();
+    }
+}
+
+fn update_phase(
+    time: Res<Time>,
+    round: Option<Res<Round>>,
+    clock: Res<ChainClock>,
+    phase: Res<State<RoundPhase>>,
+    mut next_phase: ResMut<NextState<RoundPhase>>,
+) {
+    let next = round.map_or(RoundPhase::Unscheduled, |round| round.phase(&clock, &time));
+    if next != *phase.get() {
+        next_phase.set(next);
+    }
+}
+
+#[derive(Component)]
+struct CountdownText;
+
+fn spawn_countdown(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                top: Val::Px(10.0),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load(FONT),
+                        font_size: 26.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                CountdownText,
+            ));
+        });
+}
+
+fn update_countdown(
+    time: Res<Time>,
+    round: Option<Res<Round>>,
+    clock: Res<ChainClock>,
+    phase: Res<State<RoundPhase>>,
+    mut texts: Query<&mut Text, With<CountdownText>>,
+) {
+    let phase = *phase.get();
+    let label = match &round {
+        None => String::new(),
+        Some(round) => {
+            let countdown = round.countdown(phase, &clock, &time);
+            match (phase, countdown) {
+                (RoundPhase::Over, _) => format!("Round {} is over", round.number),
+                (RoundPhase::Running, Some(left)) => {
+                    format!("Round {} - {} left", round.number, left)
+                }
+                (_, Some(left)) => format!("Round {} starts in {}", round.number, left),
+                (_, None) => format!("Round {} - waiting for the chain clock", round.number),
+            }
+        }
+    };
+
+    for mut text in &mut texts {
+        if text.sections[0].value != label {
+            text.sections[0].value.clone_from(&label);
+        }
+    }
+}
+
+fn announce_end(round: Option<Res<Round>>, mut game_events: EventWriter<GameEvent>) {
+    if let Some(round) = round {
+        info!("Round {} is over", round.number);
+        game_events.send(GameEvent::RoundEnded {
+            round: round.number,
+        });
+    }
+}
+
+#[derive(Component)]
+struct ResultsScreen;
+
+/// Full-screen summary naming the leaders of every victory when the round ends.
+/// Rebuilt if final leaderboards arrive after the end.
+fn show_results(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    leaderboards: Res<Leaderboards>,
+    round: Option<Res<Round>>,
+    screens: Query<Entity, With<ResultsScreen>>,
+) {
+    for screen in &screens {
+        commands.entity(screen).despawn_recursive();
+    }
+
+    let font = asset_server.load(FONT);
+    let style = |font_size: f32, color: Color| TextStyle {
+        font: font.clone(),
+        font_size,
+        color,
+    };
+    let title = match round {
+        Some(round) => format!("Round {} is over", round.number),
+        None => "The round is over".to_string(),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(16.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+                // Above the map and the ledger panel.
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            ResultsScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(title, style(40.0, Color::WHITE)));
+
+            for victory in Victory::ALL {
+                let winners: Vec<String> = leaderboards
+                    .ranked(victory)
+                    .into_iter()
+                    .take_while(|(rank, _)| *rank == 1)
+                    .map(|(_, player)| {
+                        format!("{} ({})", shorten_address(&player.address), player.score)
+                    })
+                    .collect();
+                let line = if winners.is_empty() {
+                    "no winner".to_string()
+                } else {
+                    winners.join(", ")
+                };
+
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|category| {
+                        category.spawn(TextBundle::from_section(
+                            victory.title(),
+                            style(28.0, Color::GOLD),
+                        ));
+                        category.spawn(TextBundle::from_section(line, style(22.0, Color::WHITE)));
+                    });
+            }
+        });
+}
+
+fn hide_results(mut commands: Commands, screens: Query<Entity, With<ResultsScreen>>) {
+    for screen in &screens {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+/// Run condition for gameplay that stops once the round is over.
+pub fn round_open(phase: Res<State<RoundPhase>>) -> bool {
+    *phase.get() != RoundPhase::Over
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    build::{Settlements, StructureKind, STRUCTURE_Z},
+    chain::{bridge::WorldObjectsReceived, ledger::LocalPlayer},
+    map::{cell_to_world, TILE_SIZE},
+};
+
+/// Structures of other players are drawn slightly darker than the local player's.
+const OTHER_PLAYER_TINT: Color = Color::rgb(0.8, 0.8, 0.8);
+
+/// A structure registered on chain, as the web app reads it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WorldObject {
+    /// Sui object ID; identifies the structure across updates.
+    pub id: String,
+    pub owner: String,
+    pub structure: StructureKind,
+    pub x: i32,
+    pub y: i32,
+    pub level: u32,
+}
+
+impl WorldObject {
+    pub fn cell(&self) -> IVec2 {
+        IVec2::new(self.x, self.y)
+    }
+}
+
+pub struct SettlementPlugin;
+
+impl Plugin for SettlementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldObjects>()
+            .add_systems(Update, (sync_world_objects, tint_world_objects).chain());
+    }
+}
+
+/// Structures on chain and the sprites showing them, by object ID.
+#[derive(Resource, Default)]
+pub struct WorldObjects {
+    objects: HashMap<String, (WorldObject, Entity)>,
+    tiles: HashMap<IVec2, String>,
+}
+
+impl WorldObjects {
+    pub fn at(&self, cell: IVec2) -> Option<&WorldObject> {
+        let id = self.tiles.get(&cell)?;
+        self.objects.get(id).map(|(object, _)| object)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &WorldObject> {
+        self.objects.values().map(|(object, _)| object)
+    }
+}
+
+#[derive(Component)]
+struct WorldObjectSprite {
+    owner: String,
+}
+
+#[derive(Component)]
+struct LevelLabel;
+
+/// Applies the latest object list: new objects are spawned, changed ones updated in
+/// place and missing ones despawned.
+fn sync_world_objects(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut received: EventReader<WorldObjectsReceived>,
+    mut world: ResMut<WorldObjects>,
+    mut settlements: ResMut<Settlements>,
+    mut sprites: Query<(&mut Transform, &mut Handle<Image>, &mut WorldObjectSprite)>,
+    mut labels: Query<(&Parent, &mut Text), With<LevelLabel>>,
+) {
+    let Some(WorldObjectsReceived(incoming)) = received.read().last() else {
+        return;
+    };
+    let world = &mut *world;
+
+    let mut previous = std::mem::take(&mut world.objects);
+    world.tiles.clear();
+
+    for object in incoming {
+        // Our own placement made it on chain.
+        if let Some(pending) = settlements.take(object.cell()) {
+            commands.entity(pending).despawn();
+        }
+
+        let entity = match previous.remove(&object.id) {
+            Some((old, entity)) if old == *object => entity,
+            Some((_, entity)) => {
+                if let Ok((mut transform, mut texture, mut sprite)) = sprites.get_mut(entity) {
+                    transform.translation = cell_to_world(object.cell()).extend(STRUCTURE_Z);
+                    *texture = asset_server.load(object.structure.texture());
+                    sprite.owner.clone_from(&object.owner);
+                }
+                for (parent, mut text) in &mut labels {
+                    if parent.get() == entity {
+                        text.sections[0].value = object.level.to_string();
+                    }
+                }
+                entity
+            }
+            None => spawn_world_object(&mut commands, &asset_server, object),
+        };
+
+        world.tiles.insert(object.cell(), object.id.clone());
+        world
+            .objects
+            .insert(object.id.clone(), (object.clone(), entity));
+    }
+
+    for (_, entity) in previous.into_values() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn spawn_world_object(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    object: &WorldObject,
+) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(TILE_SIZE)),
+                    ..default()
+                },
+                texture: asset_server.load(object.structure.texture()),
+                transform: Transform::from_translation(
+                    cell_to_world(object.cell()).extend(STRUCTURE_Z),
+                ),
+                ..default()
+            },
+            WorldObjectSprite {
+                owner: object.owner.clone(),
+            },
+        ))
+        .with_children(|parent| {
+            // Level in the bottom right corner of the tile.
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        object.level.to_string(),
+                        TextStyle {
+                            font: asset_server.load("PoetsenOne-Regular.ttf"),
+                            font_size: 14.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    transform: Transform::from_xyz(TILE_SIZE * 0.35, -TILE_SIZE * 0.35, 0.01),
+                    ..default()
+                },
+                LevelLabel,
+            ));
+        })
+        .id()
+}
+
+fn tint_world_objects(
+    local_player: Res<LocalPlayer>,
+    mut sprites: Query<(Ref<WorldObjectSprite>, &mut Sprite)>,
+) {
+    for (object, mut sprite) in &mut sprites {
+        if !local_player.is_changed() && !object.is_changed() {
+            continue;
+        }
+        sprite.color = if local_player.is(&object.owner) {
+            Color::WHITE
+        } else {
+            OTHER_PLAYER_TINT
+        };
+    }
+}
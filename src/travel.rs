@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    chain::bridge::GameEvent,
+    helpers::touch::Tap,
+    map::{cell_to_world, pathfinding::find_path, world_to_cell, Terrain},
+    AppState, Game, PLAYER_Z,
+};
+
+/// Tiles A* may expand for one click before the destination counts as unreachable.
+const MAX_SEARCH_NODES: usize = 20_000;
+
+/// Draw order of the route overlay, between the terrain and the player.
+const ROUTE_Z: f32 = 0.5;
+
+const ROUTE_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.6);
+const DESTINATION_COLOR: Color = Color::rgba(1.0, 0.85, 0.2, 0.8);
+
+pub struct TravelPlugin;
+
+impl Plugin for TravelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Route>()
+            .add_systems(
+                Update,
+                (plan_route.run_if(crate::build::not_building), follow_route)
+                    .chain()
+                    .after(crate::player_movement)
+                    .run_if(in_state(AppState::Finished))
+                    .run_if(crate::round::round_open),
+            )
+            .add_systems(OnExit(AppState::Finished), clear_route);
+    }
+}
+
+/// Tiles the player still has to walk to reach the clicked destination.
+#[derive(Resource, Default)]
+pub struct Route {
+    steps: VecDeque<IVec2>,
+    /// Tile the player sprite is currently animating away from.
+    leaving: Option<IVec2>,
+}
+
+impl Route {
+    pub fn cancel(&mut self) {
+        self.steps.clear();
+        self.leaving = None;
+    }
+}
+
+#[derive(Component)]
+struct RouteMarker {
+    cell: IVec2,
+}
+
+/// Plans a route to the tile under the cursor when the map is clicked or tapped.
+#[allow(clippy::too_many_arguments)]
+fn plan_route(
+    mut commands: Commands,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    tap: Res<Tap>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    interactions: Query<&Interaction>,
+    terrain: Terrain,
+    game: Res<Game>,
+    mut route: ResMut<Route>,
+    markers: Query<Entity, With<RouteMarker>>,
+    mut game_events: EventWriter<GameEvent>,
+) {
+    let pointer = if mouse_buttons.just_pressed(MouseButton::Left) {
+        // Clicks on UI buttons are not meant for the map.
+        if interactions.iter().any(|i| *i != Interaction::None) {
+            return;
+        }
+        windows.get_single().ok().and_then(|w| w.cursor_position())
+    } else {
+        tap.position()
+    };
+    let Some(cursor) = pointer else {
+        return;
+    };
+    let Some(target) = cameras
+        .iter()
+        .find_map(|(camera, transform)| camera.viewport_to_world_2d(transform, cursor))
+    else {
+        return;
+    };
+
+    let goal = world_to_cell(target);
+    game_events.send(GameEvent::TileClicked {
+        x: goal.x,
+        y: goal.y,
+    });
+
+    let Some(path) = find_path(
+        game.player.cell.into(),
+        goal.into(),
+        |cell| terrain.movement_cost(cell.into()),
+        MAX_SEARCH_NODES,
+    ) else {
+        info!("No route to tile {}", goal);
+        return;
+    };
+
+    for entity in markers.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    route.steps = path.into_iter().skip(1).map(IVec2::from).collect();
+    for (i, cell) in route.steps.iter().enumerate() {
+        let destination = i + 1 == route.steps.len();
+        let (color, size) = if destination {
+            (DESTINATION_COLOR, 16.0)
+        } else {
+            (ROUTE_COLOR, 8.0)
+        };
+
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(size)),
+                    ..default()
+                },
+                transform: Transform::from_translation(cell_to_world(*cell).extend(ROUTE_Z)),
+                ..default()
+            },
+            RouteMarker { cell: *cell },
+        ));
+    }
+}
+
+/// Walks the player along the planned route, one tile per movement cooldown, sliding
+/// the sprite between tiles.
+fn follow_route(
+    mut commands: Commands,
+    terrain: Terrain,
+    mut game: ResMut<Game>,
+    mut route: ResMut<Route>,
+    markers: Query<(Entity, &RouteMarker)>,
+    mut transforms: Query<&mut Transform, Without<RouteMarker>>,
+) {
+    let player = &mut game.player;
+
+    if player.move_cooldown.finished() {
+        match route.steps.pop_front() {
+            // The palette may have changed since the route was planned.
+            Some(next) if terrain.is_walkable(next) => {
+                route.leaving = Some(player.cell);
+                player.cell = next;
+                player.move_cooldown.reset();
+            }
+            _ => route.cancel(),
+        }
+
+        for (entity, marker) in markers.iter() {
+            if route.leaving.is_none() || marker.cell == player.cell {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+
+    let Some(from) = route.leaving else {
+        return;
+    };
+    if let Some(mut transform) = player.entity.and_then(|e| transforms.get_mut(e).ok()) {
+        let progress = player.move_cooldown.fraction();
+        let position = cell_to_world(from).lerp(cell_to_world(player.cell), progress);
+        transform.translation = position.extend(PLAYER_Z);
+    }
+}
+
+fn clear_route(
+    mut commands: Commands,
+    mut route: ResMut<Route>,
+    markers: Query<Entity, With<RouteMarker>>,
+) {
+    route.cancel();
+    for entity in markers.iter() {
+        commands.entity(entity).despawn();
+    }
+}
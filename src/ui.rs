@@ -1,6 +1,10 @@
 use crate::AppState;
 use bevy::prelude::*;
 
+pub mod ledger;
+pub mod minimap;
+pub mod settings;
+
 const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
 const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
@@ -9,11 +13,17 @@ pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::Setup), setup_button)
-            // .add_systems(Update, setup_ledger_menu_buttons.run_if(in_state(AppState::Ledger)))
-            .add_systems(Update, button_system) //button_system.run_if(in_state(AppState::Finished)))
-            .add_event::<ResetMapEvent>()
-            .add_event::<ShowLedgerEvent>();
+        app.add_plugins((
+            ledger::LedgerUiPlugin,
+            minimap::MinimapPlugin,
+            settings::SettingsUiPlugin,
+        ))
+        .add_systems(OnEnter(AppState::Setup), setup_button)
+        // .add_systems(Update, setup_ledger_menu_buttons.run_if(in_state(AppState::Ledger)))
+        .add_systems(Update, button_system) //button_system.run_if(in_state(AppState::Finished)))
+        .add_event::<ResetMapEvent>()
+        .add_event::<ShowLedgerEvent>()
+        .add_event::<ToggleSettingsEvent>();
     }
 }
 
@@ -22,6 +32,7 @@ impl Plugin for UiPlugin {
 pub enum MenuButtonAction {
     Reset,  // Reset the map
     Ledger, // Leaderboard
+    Keys,   // Input bindings
 }
 
 #[derive(Event)]
@@ -30,6 +41,9 @@ pub struct ResetMapEvent;
 #[derive(Event)]
 pub struct ShowLedgerEvent(pub bool);
 
+#[derive(Event)]
+pub struct ToggleSettingsEvent;
+
 pub fn button_system(
     mut interaction_query: Query<
         (
@@ -42,6 +56,7 @@ pub fn button_system(
     >,
     mut reset_events: EventWriter<ResetMapEvent>,
     mut ledger_events: EventWriter<ShowLedgerEvent>,
+    mut settings_events: EventWriter<ToggleSettingsEvent>,
     mut mouse_buttons: ResMut<ButtonInput<MouseButton>>,
     state: Res<State<AppState>>,
 ) {
@@ -65,6 +80,9 @@ pub fn button_system(
                         dbg!("CLICKED LEDGER");
                         ledger_events.send(ShowLedgerEvent(true));
                     }
+                    MenuButtonAction::Keys => {
+                        settings_events.send(ToggleSettingsEvent);
+                    }
                 }
             }
             Interaction::Hovered => {
@@ -164,6 +182,36 @@ pub fn setup_button(
                         },
                     ));
                 });
+
+            // Key bindings button
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(80.0),
+                            height: Val::Px(45.0),
+                            border: UiRect::all(Val::Px(3.0)),
+                            margin: UiRect::all(Val::Px(5.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        border_color: BorderColor(Color::BLACK),
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    },
+                    MenuButtonAction::Keys,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Keys",
+                        TextStyle {
+                            font: asset_server.load("PoetsenOne-Regular.ttf"),
+                            font_size: 30.0,
+                            color: Color::rgb(0.9, 0.9, 0.9),
+                        },
+                    ));
+                });
         });
     // next_state.set(AppState::Build);
 }
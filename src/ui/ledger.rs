@@ -0,0 +1,722 @@
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        mouse::{MouseScrollUnit, MouseWheel},
+        ButtonState,
+    },
+    prelude::*,
+};
+
+use super::{HOVERED_BUTTON, NORMAL_BUTTON};
+use crate::{
+    chain::ledger::{shorten_address, Leaderboards, LocalPlayer, Player, Victory},
+    AppState,
+};
+
+/// Rows shown on one page of a table.
+const PAGE_SIZE: usize = 20;
+
+const ROW_WIDTH: f32 = 480.0;
+const ROW_HEIGHT: f32 = 26.0;
+const RANK_COLUMN: f32 = 60.0;
+const SCORE_COLUMN: f32 = 140.0;
+
+/// Pixels scrolled per mouse wheel line.
+const SCROLL_LINE: f32 = 20.0;
+
+const LOCAL_PLAYER_ROW: Color = Color::rgb(0.35, 0.3, 0.1);
+const SELECTED_TAB: Color = Color::rgb(0.35, 0.35, 0.35);
+const EDITING_FILTER: Color = Color::rgb(0.2, 0.3, 0.45);
+
+const FONT: &str = "PoetsenOne-Regular.ttf";
+
+pub struct LedgerUiPlugin;
+
+impl Plugin for LedgerUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TableRoot>()
+            .init_resource::<LedgerView>()
+            .add_systems(OnEnter(AppState::Ledger), show_ledger)
+            .add_systems(OnExit(AppState::Ledger), hide_ledger)
+            .add_systems(
+                Update,
+                (
+                    tab_click_system,
+                    control_system,
+                    filter_input,
+                    (highlight_tabs, render_table).run_if(table_changed),
+                    scroll_rows,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Ledger)),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct TableRoot {
+    panel: Entity,
+    /// Container the controls, rows and pager of the selected table are rendered into.
+    body: Entity,
+}
+
+impl Default for TableRoot {
+    fn default() -> Self {
+        TableRoot {
+            panel: Entity::PLACEHOLDER,
+            body: Entity::PLACEHOLDER,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum SortColumn {
+    #[default]
+    Score,
+    Address,
+}
+
+/// What the ledger panel shows; survives closing and reopening the panel.
+#[derive(Resource, Default)]
+struct LedgerView {
+    victory: Victory,
+    sort: SortColumn,
+    ascending: bool,
+    /// Case-insensitive substring an address must contain to be listed.
+    filter: String,
+    editing_filter: bool,
+    page: usize,
+    /// Address to scroll into view on the next render.
+    focus: Option<String>,
+}
+
+impl LedgerView {
+    /// Rows of the selected table after filtering and sorting, with their score rank.
+    fn rows<'a>(&self, leaderboards: &'a Leaderboards) -> Vec<(usize, &'a Player)> {
+        let filter = self.filter.trim().to_ascii_lowercase();
+        let mut rows: Vec<_> = leaderboards
+            .ranked(self.victory)
+            .into_iter()
+            .filter(|(_, player)| {
+                filter.is_empty() || player.address.to_ascii_lowercase().contains(&filter)
+            })
+            .collect();
+
+        match self.sort {
+            SortColumn::Score if self.ascending => rows.reverse(),
+            SortColumn::Score => {}
+            SortColumn::Address => {
+                rows.sort_by(|(_, a), (_, b)| a.address.cmp(&b.address));
+                if !self.ascending {
+                    rows.reverse();
+                }
+            }
+        }
+        rows
+    }
+
+    fn sort_by(&mut self, column: SortColumn) {
+        if self.sort == column {
+            self.ascending = !self.ascending;
+        } else {
+            self.sort = column;
+            // Best scores and `0x0…` addresses first.
+            self.ascending = column == SortColumn::Address;
+        }
+        self.page = 0;
+    }
+}
+
+fn page_count(rows: usize) -> usize {
+    rows.div_ceil(PAGE_SIZE).max(1)
+}
+
+#[derive(Component)]
+struct Tab {
+    victory: Victory,
+}
+
+#[derive(Component)]
+struct TableRow;
+
+#[derive(Component, Clone, Copy)]
+enum LedgerControl {
+    Sort(SortColumn),
+    PreviousPage,
+    NextPage,
+    Filter,
+    JumpToMe,
+}
+
+/// List of rows clipped by its parent and moved with the mouse wheel.
+#[derive(Component, Default)]
+struct ScrollingList {
+    position: f32,
+}
+
+// Economic Victory, Cultural Victory, Diplomatic Victory
+fn show_ledger(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut view: ResMut<LedgerView>,
+) {
+    let table_root = commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(50.0),
+                height: Val::Percent(80.0),
+                left: Val::Percent(25.0),
+                top: Val::Percent(10.0),
+                bottom: Val::Percent(10.0),
+                right: Val::Percent(25.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::FlexStart,
+                border: UiRect {
+                    left: Val::Px(2.0),
+                    right: Val::Px(2.0),
+                    top: Val::Px(2.0),
+                    bottom: Val::Px(2.0),
+                },
+                ..default()
+            },
+            background_color: Color::DARK_GRAY.into(),
+            border_color: Color::WHITE.into(),
+            ..default()
+        })
+        .id();
+
+    let mut body = Entity::PLACEHOLDER;
+    commands.entity(table_root).with_children(|parent| {
+        // Header
+        parent.spawn(TextBundle {
+            text: Text::from_section(
+                "Ledger Stats",
+                TextStyle {
+                    font: asset_server.load(FONT),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+            ),
+            ..default()
+        });
+
+        // Tabs for switching tables
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(300.0),
+                    height: Val::Px(50.0),
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|tabs| {
+                for victory in Victory::ALL {
+                    tabs.spawn(ButtonBundle {
+                        style: Style {
+                            width: Val::Percent(33.0),
+                            height: Val::Percent(100.0),
+                            // Styling for each tab button
+                            ..default()
+                        },
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    })
+                    .insert(Tab { victory })
+                    .with_children(|button| {
+                        button.spawn(TextBundle {
+                            text: Text::from_section(
+                                victory.title(),
+                                TextStyle {
+                                    font: asset_server.load(FONT),
+                                    font_size: 20.0,
+                                    color: Color::WHITE,
+                                },
+                            ),
+                            ..default()
+                        });
+                    });
+                }
+            });
+
+        body = parent
+            .spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(ROW_WIDTH),
+                    flex_grow: 1.0,
+                    min_height: Val::Px(0.0),
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+    });
+
+    commands.insert_resource(TableRoot {
+        panel: table_root,
+        body,
+    });
+
+    // Draw the table the panel was last left on.
+    view.editing_filter = false;
+    view.set_changed();
+}
+
+fn hide_ledger(mut commands: Commands, table_root: Res<TableRoot>) {
+    commands.entity(table_root.panel).despawn_recursive();
+}
+
+fn table_changed(
+    view: Res<LedgerView>,
+    leaderboards: Res<Leaderboards>,
+    local_player: Res<LocalPlayer>,
+) -> bool {
+    view.is_changed() || leaderboards.is_changed() || local_player.is_changed()
+}
+
+fn tab_click_system(
+    mut view: ResMut<LedgerView>,
+    interaction_query: Query<(&Interaction, &Tab), Changed<Interaction>>,
+) {
+    for (interaction, tab) in interaction_query.iter() {
+        if *interaction == Interaction::Pressed {
+            view.victory = tab.victory;
+            view.page = 0;
+            view.focus = None;
+        }
+    }
+}
+
+fn highlight_tabs(view: Res<LedgerView>, mut tabs: Query<(&Tab, &mut BackgroundColor)>) {
+    for (tab, mut color) in &mut tabs {
+        *color = if tab.victory == view.victory {
+            SELECTED_TAB.into()
+        } else {
+            NORMAL_BUTTON.into()
+        };
+    }
+}
+
+fn control_system(
+    mut view: ResMut<LedgerView>,
+    leaderboards: Res<Leaderboards>,
+    local_player: Res<LocalPlayer>,
+    mut interaction_query: Query<
+        (&Interaction, &LedgerControl, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+) {
+    for (interaction, control, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {}
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+                continue;
+            }
+            Interaction::None => {
+                *color = match control {
+                    LedgerControl::Filter if view.editing_filter => EDITING_FILTER.into(),
+                    _ => NORMAL_BUTTON.into(),
+                };
+                continue;
+            }
+        }
+
+        match control {
+            LedgerControl::Sort(column) => view.sort_by(*column),
+            LedgerControl::PreviousPage => view.page = view.page.saturating_sub(1),
+            LedgerControl::NextPage => {
+                let last = page_count(view.rows(&leaderboards).len()) - 1;
+                view.page = (view.page + 1).min(last);
+            }
+            LedgerControl::Filter => view.editing_filter = !view.editing_filter,
+            LedgerControl::JumpToMe => {
+                let Some(address) = local_player.0.clone() else {
+                    info!("No local player to jump to");
+                    continue;
+                };
+                view.filter.clear();
+                view.editing_filter = false;
+                let rows = view.rows(&leaderboards);
+                // Focus the row's own spelling of the address, which the table matches.
+                let found = rows
+                    .iter()
+                    .position(|(_, player)| local_player.is(&player.address))
+                    .map(|index| (index, rows[index].1.address.clone()));
+                match found {
+                    Some((index, focus)) => {
+                        view.page = index / PAGE_SIZE;
+                        view.focus = Some(focus);
+                    }
+                    None => info!(
+                        "{} is not on the {} leaderboard",
+                        address,
+                        view.victory.title()
+                    ),
+                }
+            }
+        }
+    }
+}
+
+/// Types into the address filter while it has focus.
+fn filter_input(mut view: ResMut<LedgerView>, mut keys: EventReader<KeyboardInput>) {
+    if !view.editing_filter {
+        keys.clear();
+        return;
+    }
+
+    for key in keys.read() {
+        if key.state != ButtonState::Pressed {
+            continue;
+        }
+        match &key.logical_key {
+            Key::Character(text) => view.filter.push_str(text),
+            Key::Space => view.filter.push(' '),
+            Key::Backspace => {
+                view.filter.pop();
+            }
+            Key::Enter | Key::Escape => view.editing_filter = false,
+            _ => continue,
+        }
+        view.page = 0;
+    }
+}
+
+fn render_table(
+    mut commands: Commands,
+    table_root: Res<TableRoot>,
+    asset_server: Res<AssetServer>,
+    leaderboards: Res<Leaderboards>,
+    local_player: Res<LocalPlayer>,
+    mut view: ResMut<LedgerView>,
+) {
+    let font: Handle<Font> = asset_server.load(FONT);
+    let rows = view.rows(&leaderboards);
+    let pages = page_count(rows.len());
+
+    // Keep the page valid when the table shrinks, without rendering twice.
+    let view = view.bypass_change_detection();
+    view.page = view.page.min(pages - 1);
+    let focus = view.focus.take();
+
+    let page: Vec<_> = rows
+        .iter()
+        .skip(view.page * PAGE_SIZE)
+        .take(PAGE_SIZE)
+        .collect();
+    let scroll = focus
+        .and_then(|address| {
+            page.iter()
+                .position(|(_, player)| player.address == address)
+        })
+        .map_or(0.0, |index| -(index as f32) * ROW_HEIGHT);
+
+    commands
+        .entity(table_root.body)
+        .despawn_descendants()
+        .with_children(|parent| {
+            spawn_controls(parent, view, &font);
+            spawn_header(parent, view, &font);
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        flex_grow: 1.0,
+                        min_height: Val::Px(0.0),
+                        flex_direction: FlexDirection::Column,
+                        overflow: Overflow::clip_y(),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|viewport| {
+                    viewport
+                        .spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Percent(100.0),
+                                    top: Val::Px(scroll),
+                                    flex_direction: FlexDirection::Column,
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            ScrollingList { position: scroll },
+                        ))
+                        .with_children(|list| {
+                            if page.is_empty() {
+                                let message = if view.filter.trim().is_empty() {
+                                    "No scores yet"
+                                } else {
+                                    "No players match the filter"
+                                };
+                                list.spawn(text(message, &font, Color::GRAY));
+                            }
+
+                            for (rank, player) in page {
+                                let background = if local_player.is(&player.address) {
+                                    LOCAL_PLAYER_ROW
+                                } else {
+                                    Color::NONE
+                                };
+                                spawn_row(list, *rank, player, background, &font);
+                            }
+                        });
+                });
+
+            spawn_pager(parent, view.page, pages, &font);
+        });
+}
+
+fn text(value: impl Into<String>, font: &Handle<Font>, color: Color) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font: font.clone(),
+            font_size: 20.0,
+            color,
+        },
+    )
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    control: LedgerControl,
+    label: impl Into<String>,
+    width: Val,
+    background: Color,
+    font: &Handle<Font>,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width,
+                    height: Val::Px(ROW_HEIGHT),
+                    padding: UiRect::horizontal(Val::Px(4.0)),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: background.into(),
+                ..default()
+            },
+            control,
+        ))
+        .with_children(|button| {
+            button.spawn(text(label, font, Color::WHITE));
+        });
+}
+
+fn spawn_controls(parent: &mut ChildBuilder, view: &LedgerView, font: &Handle<Font>) {
+    let (label, background) = if view.editing_filter {
+        (format!("Filter: {}_", view.filter), EDITING_FILTER)
+    } else if view.filter.is_empty() {
+        ("Filter by address".to_string(), NORMAL_BUTTON)
+    } else {
+        (format!("Filter: {}", view.filter), NORMAL_BUTTON)
+    };
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::SpaceBetween,
+                margin: UiRect::vertical(Val::Px(4.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|controls| {
+            spawn_button(
+                controls,
+                LedgerControl::Filter,
+                label,
+                Val::Px(ROW_WIDTH - 130.0),
+                background,
+                font,
+            );
+            spawn_button(
+                controls,
+                LedgerControl::JumpToMe,
+                "Jump to me",
+                Val::Px(120.0),
+                NORMAL_BUTTON,
+                font,
+            );
+        });
+}
+
+fn spawn_header(parent: &mut ChildBuilder, view: &LedgerView, font: &Handle<Font>) {
+    let label = |name: &str, column: SortColumn| match (view.sort == column, view.ascending) {
+        (false, _) => name.to_string(),
+        (true, true) => format!("{} ^", name),
+        (true, false) => format!("{} v", name),
+    };
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Row,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|header| {
+            header
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(RANK_COLUMN),
+                        padding: UiRect::horizontal(Val::Px(4.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|cell| {
+                    cell.spawn(text("Rank", font, Color::WHITE));
+                });
+            spawn_button(
+                header,
+                LedgerControl::Sort(SortColumn::Address),
+                label("Player", SortColumn::Address),
+                Val::Px(ROW_WIDTH - RANK_COLUMN - SCORE_COLUMN),
+                NORMAL_BUTTON,
+                font,
+            );
+            spawn_button(
+                header,
+                LedgerControl::Sort(SortColumn::Score),
+                label("Score", SortColumn::Score),
+                Val::Px(SCORE_COLUMN),
+                NORMAL_BUTTON,
+                font,
+            );
+        });
+}
+
+fn spawn_row(
+    parent: &mut ChildBuilder,
+    rank: usize,
+    player: &Player,
+    background: Color,
+    font: &Handle<Font>,
+) {
+    let cells = [
+        (rank.to_string(), RANK_COLUMN),
+        (
+            shorten_address(&player.address),
+            ROW_WIDTH - RANK_COLUMN - SCORE_COLUMN,
+        ),
+        (player.score.to_string(), SCORE_COLUMN),
+    ];
+
+    parent
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(ROW_HEIGHT),
+                    flex_shrink: 0.0,
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: background.into(),
+                ..default()
+            },
+            TableRow,
+        ))
+        .with_children(|row| {
+            for (cell, width) in cells {
+                row.spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(width),
+                        padding: UiRect::horizontal(Val::Px(4.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|column| {
+                    column.spawn(text(cell, font, Color::GRAY));
+                });
+            }
+        });
+}
+
+fn spawn_pager(parent: &mut ChildBuilder, page: usize, pages: usize, font: &Handle<Font>) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(12.0),
+                margin: UiRect::vertical(Val::Px(4.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|pager| {
+            spawn_button(
+                pager,
+                LedgerControl::PreviousPage,
+                "<",
+                Val::Px(30.0),
+                NORMAL_BUTTON,
+                font,
+            );
+            pager.spawn(text(
+                format!("Page {} of {}", page + 1, pages),
+                font,
+                Color::WHITE,
+            ));
+            spawn_button(
+                pager,
+                LedgerControl::NextPage,
+                ">",
+                Val::Px(30.0),
+                NORMAL_BUTTON,
+                font,
+            );
+        });
+}
+
+/// Scrolls the rows of the current page and keeps them inside their viewport.
+fn scroll_rows(
+    mut wheel: EventReader<MouseWheel>,
+    mut lists: Query<(&mut ScrollingList, &mut Style, &Parent, &Node)>,
+    viewports: Query<&Node, Without<ScrollingList>>,
+) {
+    let delta: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * SCROLL_LINE,
+            MouseScrollUnit::Pixel => event.y,
+        })
+        .sum();
+
+    for (mut list, mut style, parent, node) in &mut lists {
+        let Ok(viewport) = viewports.get(parent.get()) else {
+            continue;
+        };
+        // Not laid out yet; clamping now would undo a "jump to me" scroll.
+        if node.size() == Vec2::ZERO || viewport.size() == Vec2::ZERO {
+            continue;
+        }
+        let max_scroll = (node.size().y - viewport.size().y).max(0.0);
+        let position = (list.position + delta).clamp(-max_scroll, 0.0);
+        if position != list.position {
+            list.position = position;
+            style.top = Val::Px(position);
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    ui::RelativeCursorPosition,
+};
+
+use crate::{
+    build::Settlements,
+    fog::Fog,
+    helpers::camera::{CameraControl, CameraController},
+    map::{cell_corner, Terrain, WorldNoise, CHUNK_SIZE, TILE_SIZE},
+    roster::Unit,
+    settlement::WorldObjects,
+    AppState, Game,
+};
+
+/// Edge length of the minimap image; every pixel stands for `TILES_PER_PIXEL`² tiles.
+const PIXELS: i32 = 128;
+const TILES_PER_PIXEL: i32 = 2;
+/// Tiles along each edge of the area the minimap shows.
+const SPAN: i32 = PIXELS * TILES_PER_PIXEL;
+
+/// On-screen edge length of the minimap.
+const MINIMAP_SIZE: f32 = 192.0;
+
+/// The shown area follows the player once they are this many tiles from its centre.
+const RECENTRE_DISTANCE: i32 = SPAN / 4;
+
+/// Seconds between repaints of the markers and the fog.
+const REFRESH_INTERVAL: f32 = 0.25;
+
+const UNEXPLORED: [u8; 4] = [0, 0, 0, 255];
+const PLAYER_MARKER: [u8; 4] = [255, 255, 255, 255];
+const UNIT_MARKER: [u8; 4] = [220, 40, 40, 255];
+const SETTLEMENT_MARKER: [u8; 4] = [255, 215, 0, 255];
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_minimap).add_systems(
+            Update,
+            (
+                (follow_player, sample_terrain, paint_minimap).chain(),
+                jump_camera.before(CameraControl),
+                update_viewport.after(CameraControl),
+            )
+                .run_if(in_state(AppState::Finished)),
+        );
+    }
+}
+
+/// Downscaled view of the terrain around the player.
+#[derive(Resource)]
+struct Minimap {
+    image: Handle<Image>,
+    /// Tile in the middle of the shown area.
+    centre: IVec2,
+    /// Colour of every pixel's terrain, top row first; empty until sampled.
+    terrain: Vec<[u8; 4]>,
+    refresh: Timer,
+    /// Set to repaint without waiting for `refresh`.
+    repaint: bool,
+}
+
+impl Minimap {
+    /// Bottom left tile of the shown area.
+    fn first_cell(&self) -> IVec2 {
+        self.centre - SPAN / 2
+    }
+
+    /// Tile a pixel is coloured after.
+    fn pixel_cell(&self, x: i32, y: i32) -> IVec2 {
+        self.first_cell() + IVec2::new(x, PIXELS - 1 - y) * TILES_PER_PIXEL
+    }
+
+    fn cell_pixel(&self, cell: IVec2) -> Option<(i32, i32)> {
+        let offset = (cell - self.first_cell()).div_euclid(IVec2::splat(TILES_PER_PIXEL));
+        let (x, y) = (offset.x, PIXELS - 1 - offset.y);
+        ((0..PIXELS).contains(&x) && (0..PIXELS).contains(&y)).then_some((x, y))
+    }
+
+    /// Position on the minimap of a world position, from (0, 0) in the top left corner
+    /// to (1, 1) in the bottom right one.
+    fn to_minimap(&self, position: Vec2) -> Vec2 {
+        let tiles = (position - cell_corner(self.first_cell())) / TILE_SIZE;
+        Vec2::new(tiles.x, SPAN as f32 - tiles.y) / SPAN as f32
+    }
+
+    fn to_world(&self, point: Vec2) -> Vec2 {
+        let tiles = Vec2::new(point.x, 1.0 - point.y) * SPAN as f32;
+        cell_corner(self.first_cell()) + tiles * TILE_SIZE
+    }
+}
+
+#[derive(Component)]
+struct MinimapView;
+
+#[derive(Component)]
+struct ViewportFrame;
+
+fn spawn_minimap(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = images.add(Image::new_fill(
+        Extent3d {
+            width: PIXELS as u32,
+            height: PIXELS as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &UNEXPLORED,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    ));
+
+    commands
+        .spawn((
+            ImageBundle {
+                style: Style {
+                    width: Val::Px(MINIMAP_SIZE),
+                    height: Val::Px(MINIMAP_SIZE),
+                    left: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    position_type: PositionType::Absolute,
+                    border: UiRect::all(Val::Px(2.0)),
+                    overflow: Overflow::clip(),
+                    ..default()
+                },
+                image: UiImage::new(image.clone()),
+                ..default()
+            },
+            BorderColor(Color::BLACK),
+            // Also keeps clicks on the minimap from reaching the map below it.
+            Interaction::default(),
+            RelativeCursorPosition::default(),
+            MinimapView,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..default()
+                    },
+                    border_color: BorderColor(Color::WHITE),
+                    ..default()
+                },
+                ViewportFrame,
+            ));
+        });
+
+    commands.insert_resource(Minimap {
+        image,
+        centre: IVec2::ZERO,
+        terrain: Vec::new(),
+        refresh: Timer::from_seconds(REFRESH_INTERVAL, TimerMode::Repeating),
+        repaint: false,
+    });
+}
+
+/// Moves the shown area along once the player walks towards its edge.
+fn follow_player(game: Res<Game>, mut minimap: ResMut<Minimap>) {
+    let cell = game.player.cell;
+    if (cell - minimap.centre).abs().max_element() < RECENTRE_DISTANCE {
+        return;
+    }
+    // Snapped to chunks so the pixel grid does not shift with every step.
+    let size = CHUNK_SIZE as i32;
+    minimap.centre = (cell + size / 2).div_euclid(IVec2::splat(size)) * size;
+    minimap.terrain.clear();
+}
+
+/// Looks up the terrain colour of every pixel, when the area or the map changed.
+fn sample_terrain(noise: Option<Res<WorldNoise>>, terrain: Terrain, mut minimap: ResMut<Minimap>) {
+    let Some(noise) = noise else {
+        return;
+    };
+    if !noise.is_changed() && !minimap.terrain.is_empty() {
+        return;
+    }
+    let mut colors = Vec::with_capacity((PIXELS * PIXELS) as usize);
+    for y in 0..PIXELS {
+        for x in 0..PIXELS {
+            let color = terrain
+                .style(minimap.pixel_cell(x, y))
+                .map_or(UNEXPLORED, |style| style.color().as_rgba_u8());
+            colors.push(color);
+        }
+    }
+    minimap.terrain = colors;
+    minimap.repaint = true;
+}
+
+/// Draws the explored terrain and marks the player, other units and settlements.
+#[allow(clippy::too_many_arguments)]
+fn paint_minimap(
+    time: Res<Time>,
+    mut minimap: ResMut<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+    game: Res<Game>,
+    fog: Res<Fog>,
+    units: Query<&Unit>,
+    world: Res<WorldObjects>,
+    settlements: Res<Settlements>,
+) {
+    let due = minimap.refresh.tick(time.delta()).just_finished();
+    if !(due || minimap.repaint) || minimap.terrain.is_empty() {
+        return;
+    }
+    minimap.repaint = false;
+    let Some(image) = images.get_mut(&minimap.image) else {
+        return;
+    };
+
+    let mut pixels = minimap.terrain.clone();
+    for y in 0..PIXELS {
+        for x in 0..PIXELS {
+            if !fog.is_explored(minimap.pixel_cell(x, y)) {
+                pixels[(y * PIXELS + x) as usize] = UNEXPLORED;
+            }
+        }
+    }
+
+    let mut mark = |cell: IVec2, color: [u8; 4]| {
+        let Some((x, y)) = minimap.cell_pixel(cell) else {
+            return;
+        };
+        // Three pixels wide, or single tiles would be hard to see.
+        for (dx, dy) in (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| (dx, dy))) {
+            let (x, y) = (x + dx, y + dy);
+            if (0..PIXELS).contains(&x) && (0..PIXELS).contains(&y) {
+                pixels[(y * PIXELS + x) as usize] = color;
+            }
+        }
+    };
+    // Only what the player has seen is marked, like on the map.
+    let explored = |cell: &IVec2| fog.is_explored(*cell);
+    for cell in world.iter().map(|object| object.cell()).filter(explored) {
+        mark(cell, SETTLEMENT_MARKER);
+    }
+    for cell in settlements.cells() {
+        mark(cell, SETTLEMENT_MARKER);
+    }
+    for cell in units.iter().map(|unit| unit.cell).filter(explored) {
+        mark(cell, UNIT_MARKER);
+    }
+    if game.player.spawn.is_some() {
+        mark(game.player.cell, PLAYER_MARKER);
+    }
+
+    image.data = pixels.into_iter().flatten().collect();
+}
+
+/// Frames the part of the map the camera shows.
+fn update_viewport(
+    minimap: Res<Minimap>,
+    cameras: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+    mut frames: Query<&mut Style, With<ViewportFrame>>,
+) {
+    let Some((transform, projection)) = cameras.iter().next() else {
+        return;
+    };
+    let offset = transform.translation.truncate();
+    let top_left =
+        minimap.to_minimap(Vec2::new(projection.area.min.x, projection.area.max.y) + offset);
+    let bottom_right =
+        minimap.to_minimap(Vec2::new(projection.area.max.x, projection.area.min.y) + offset);
+    let size = bottom_right - top_left;
+    let rect = [top_left.x, top_left.y, size.x, size.y].map(|value| Val::Percent(value * 100.0));
+
+    for mut style in &mut frames {
+        // Only touch the style when the camera moved, or the UI is laid out every frame.
+        if [style.left, style.top, style.width, style.height] != rect {
+            [style.left, style.top, style.width, style.height] = rect;
+        }
+    }
+}
+
+/// Clicking or dragging on the minimap centres the camera there.
+fn jump_camera(
+    minimap: Res<Minimap>,
+    views: Query<(&Interaction, &RelativeCursorPosition), With<MinimapView>>,
+    mut controller: ResMut<CameraController>,
+    mut cameras: Query<&mut Transform, With<Camera>>,
+) {
+    for (interaction, cursor) in &views {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(point) = cursor.normalized else {
+            continue;
+        };
+        let target = minimap.to_world(point.clamp(Vec2::ZERO, Vec2::ONE));
+        controller.follow = false;
+        for mut transform in &mut cameras {
+            transform.translation.x = target.x;
+            transform.translation.y = target.y;
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use bevy::prelude::*;
+
+use super::{ToggleSettingsEvent, HOVERED_BUTTON, NORMAL_BUTTON};
+use crate::controls::{key_label, Action, Actions, Bindings, GamepadInput};
+
+const FONT: &str = "PoetsenOne-Regular.ttf";
+
+/// Stick axes count as a new binding once pushed this far.
+const CAPTURE_THRESHOLD: f32 = 0.5;
+
+const CAPTURING_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+
+/// Gamepad bindings have longer names than keys.
+const SLOT_WIDTHS: [(Device, f32); 2] = [(Device::Keyboard, 110.0), (Device::Gamepad, 170.0)];
+
+const STICK_AXES: [GamepadAxisType; 4] = [
+    GamepadAxisType::LeftStickX,
+    GamepadAxisType::LeftStickY,
+    GamepadAxisType::RightStickX,
+    GamepadAxisType::RightStickY,
+];
+
+pub struct SettingsUiPlugin;
+
+impl Plugin for SettingsUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SettingsPanel>().add_systems(
+            Update,
+            (toggle_panel, panel_buttons, capture_binding, update_labels).chain(),
+        );
+    }
+}
+
+/// The key bindings panel, shown over the map or the ledger.
+#[derive(Resource, Default)]
+struct SettingsPanel {
+    root: Option<Entity>,
+    /// Slot waiting for the next key or gamepad input.
+    capture: Option<Slot>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Device {
+    Keyboard,
+    Gamepad,
+}
+
+/// One binding in the panel: an action on a device.
+#[derive(Clone, Copy, PartialEq)]
+struct Slot {
+    action: Action,
+    device: Device,
+}
+
+#[derive(Component)]
+enum PanelButton {
+    Bind(Slot),
+    Defaults,
+    Close,
+}
+
+#[derive(Component)]
+struct SlotLabel(Slot);
+
+fn slot_text(slot: Slot, bindings: &Bindings, panel: &SettingsPanel) -> String {
+    if panel.capture == Some(slot) {
+        return match slot.device {
+            Device::Keyboard => "Press a key".to_string(),
+            Device::Gamepad => "Press a button".to_string(),
+        };
+    }
+    let text = match slot.device {
+        Device::Keyboard => bindings.keys.get(&slot.action).map(|key| key_label(*key)),
+        Device::Gamepad => bindings.gamepad.get(&slot.action).map(ToString::to_string),
+    };
+    text.unwrap_or_else(|| "-".to_string())
+}
+
+fn close_panel(commands: &mut Commands, panel: &mut SettingsPanel, actions: &mut Actions) {
+    if let Some(root) = panel.root.take() {
+        commands.entity(root).despawn_recursive();
+    }
+    panel.capture = None;
+    actions.enabled = true;
+}
+
+fn toggle_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut events: EventReader<ToggleSettingsEvent>,
+    bindings: Res<Bindings>,
+    mut panel: ResMut<SettingsPanel>,
+    mut actions: ResMut<Actions>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+    if panel.root.is_some() {
+        close_panel(&mut commands, &mut panel, &mut actions);
+    } else {
+        panel.root = Some(spawn_panel(&mut commands, &asset_server, &bindings, &panel));
+    }
+}
+
+fn spawn_panel(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    bindings: &Bindings,
+    panel: &SettingsPanel,
+) -> Entity {
+    let text_style = |font_size| TextStyle {
+        font: asset_server.load(FONT),
+        font_size,
+        color: TEXT_COLOR,
+    };
+    let button = |width| ButtonBundle {
+        style: Style {
+            width: Val::Px(width),
+            height: Val::Px(28.0),
+            border: UiRect::all(Val::Px(2.0)),
+            margin: UiRect::horizontal(Val::Px(4.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        border_color: BorderColor(Color::BLACK),
+        background_color: NORMAL_BUTTON.into(),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(520.0),
+                    left: Val::Percent(50.0),
+                    top: Val::Px(10.0),
+                    margin: UiRect::left(Val::Px(-260.0)),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                background_color: Color::DARK_GRAY.into(),
+                border_color: Color::WHITE.into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            // Keeps clicks on the panel from reaching the map below it.
+            Interaction::default(),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Key Bindings", text_style(30.0)));
+
+            for action in Action::ALL {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.0),
+                            margin: UiRect::vertical(Val::Px(2.0)),
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn(
+                            TextBundle::from_section(action.title(), text_style(20.0)).with_style(
+                                Style {
+                                    flex_grow: 1.0,
+                                    ..default()
+                                },
+                            ),
+                        );
+                        for (device, width) in SLOT_WIDTHS {
+                            let slot = Slot { action, device };
+                            row.spawn((button(width), PanelButton::Bind(slot)))
+                                .with_children(|button| {
+                                    button.spawn((
+                                        TextBundle::from_section(
+                                            slot_text(slot, bindings, panel),
+                                            text_style(18.0),
+                                        ),
+                                        SlotLabel(slot),
+                                    ));
+                                });
+                        }
+                    });
+            }
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        margin: UiRect::top(Val::Px(8.0)),
+                        flex_direction: FlexDirection::Row,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|footer| {
+                    for (label, action) in [
+                        ("Defaults", PanelButton::Defaults),
+                        ("Close", PanelButton::Close),
+                    ] {
+                        footer
+                            .spawn((button(110.0), action))
+                            .with_children(|button| {
+                                button.spawn(TextBundle::from_section(label, text_style(20.0)));
+                            });
+                    }
+                });
+        })
+        .id()
+}
+
+fn panel_buttons(
+    mut commands: Commands,
+    mut buttons: Query<(&Interaction, &PanelButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut mouse_buttons: ResMut<ButtonInput<MouseButton>>,
+    mut bindings: ResMut<Bindings>,
+    mut panel: ResMut<SettingsPanel>,
+    mut actions: ResMut<Actions>,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        match *interaction {
+            Interaction::Pressed => {
+                mouse_buttons.clear_just_pressed(MouseButton::Left);
+                match button {
+                    PanelButton::Bind(slot) => {
+                        panel.capture = Some(*slot);
+                        actions.enabled = false;
+                    }
+                    PanelButton::Defaults => {
+                        *bindings = Bindings::default();
+                    }
+                    PanelButton::Close => {
+                        close_panel(&mut commands, &mut panel, &mut actions);
+                        return;
+                    }
+                }
+            }
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+/// Binds the next key or gamepad input to the slot waiting for one; Escape cancels,
+/// and closes the panel when nothing is waiting.
+#[allow(clippy::too_many_arguments)]
+fn capture_binding(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut bindings: ResMut<Bindings>,
+    mut panel: ResMut<SettingsPanel>,
+    mut actions: ResMut<Actions>,
+) {
+    if panel.root.is_none() {
+        return;
+    }
+    let Some(slot) = panel.capture else {
+        if keys.just_pressed(KeyCode::Escape) {
+            close_panel(&mut commands, &mut panel, &mut actions);
+        }
+        return;
+    };
+
+    let key = keys.get_just_pressed().next().copied();
+    let done = match (key, slot.device) {
+        (Some(KeyCode::Escape), _) => true,
+        (Some(key), Device::Keyboard) => {
+            bindings.bind_key(slot.action, key);
+            true
+        }
+        (_, Device::Keyboard) => false,
+        (_, Device::Gamepad) => match pressed_gamepad_input(&gamepads, &gamepad_buttons, &axes) {
+            Some(input) => {
+                bindings.bind_gamepad(slot.action, input);
+                true
+            }
+            None => false,
+        },
+    };
+    if done {
+        panel.capture = None;
+        actions.enabled = true;
+    }
+}
+
+/// A gamepad button pressed this frame, or a stick pushed most of the way.
+fn pressed_gamepad_input(
+    gamepads: &Gamepads,
+    buttons: &ButtonInput<GamepadButton>,
+    axes: &Axis<GamepadAxis>,
+) -> Option<GamepadInput> {
+    if let Some(button) = buttons.get_just_pressed().next() {
+        return Some(GamepadInput::Button(button.button_type));
+    }
+    gamepads.iter().find_map(|gamepad| {
+        STICK_AXES.into_iter().find_map(|axis| {
+            let value = axes.get(GamepadAxis::new(gamepad, axis))?;
+            (value.abs() >= CAPTURE_THRESHOLD).then_some(GamepadInput::Axis {
+                axis,
+                positive: value > 0.0,
+            })
+        })
+    })
+}
+
+fn update_labels(
+    bindings: Res<Bindings>,
+    panel: Res<SettingsPanel>,
+    mut labels: Query<(&SlotLabel, &mut Text)>,
+    mut buttons: Query<(&PanelButton, &mut BorderColor)>,
+) {
+    if !bindings.is_changed() && !panel.is_changed() {
+        return;
+    }
+    for (label, mut text) in &mut labels {
+        text.sections[0].value = slot_text(label.0, &bindings, &panel);
+    }
+    for (button, mut border) in &mut buttons {
+        if let PanelButton::Bind(slot) = button {
+            border.0 = if panel.capture == Some(*slot) {
+                CAPTURING_BUTTON
+            } else {
+                Color::BLACK
+            };
+        }
+    }
+}
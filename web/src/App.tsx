@@ -62,20 +62,60 @@ function App() {
   const [balances, setBalances] = useState<Map<string, number>>(new Map()); // Map<Sui address, SUI balance>
   const [modalContent, setModalContent] = useState<string>("");
 
-    
-//   TODO call random after game loads. call only once.
-  test.default().then((res) => {
-    game.GetGameRandomness(suiClient).then((randomness) => {
-        console.log("Randomness: ", randomness);
-        
-        // Call game logic here
-        res.set_map_randomness(Number(randomness));
+
+    useEffect(() => {
+        // Started once per page; the cleanup stops the periodic save if the app unmounts.
+        let cancelled = false;
+        let saveInterval: ReturnType<typeof setInterval> | undefined;
+
+        test.default().then((res) => {
+            game.GetGameRandomness(suiClient).then((randomness) => {
+                if (cancelled) {
+                    return;
+                }
+                console.log("Randomness: ", randomness);
+
+                // Call game logic here
+                res.send_command({ version: 1, command: { type: "set_map_seed", seed: Number(randomness) } });
+                if (accounts.current.length > 0) {
+                    const address = accounts.current[0].userAddr;
+                    res.send_command({ version: 1, command: { type: "set_local_player", address } });
+
+                    // Fog of war: restore and keep saving what this player explored on this map.
+                    const exploredKey = `cyberspace.explored.${randomness}.${address}`;
+                    const saved = localStorage.getItem(exploredKey);
+                    if (saved) {
+                        try {
+                            res.import_explored(decodeBytes(saved));
+                        } catch (error) {
+                            // Corrupt, or saved in an older format: start exploring afresh.
+                            console.warn("Dropping saved explored tiles:", error);
+                            localStorage.removeItem(exploredKey);
+                        }
+                    }
+                    saveInterval = setInterval(() => {
+                        localStorage.setItem(exploredKey, encodeBytes(res.export_explored()));
+                    }, 10_000);
+                }
+                game.GetRegisteredPlayers(suiClient).then((addresses) => {
+                    if (!cancelled) {
+                        res.send_command({ version: 1, command: { type: "set_roster", addresses } });
+                    }
+                }).catch((error) => {
+                    console.error("Could not show the registered players:", error);
+                });
+            });
+        }).catch((error) => {
+            if (!error.message.startsWith("Using exceptions for control flow,")) {
+                throw error;
+            }
         });
-  }).catch((error) => {
-    if (!error.message.startsWith("Using exceptions for control flow,")) {
-        throw error;
-    }
-    });
+
+        return () => {
+            cancelled = true;
+            clearInterval(saveInterval);
+        };
+    }, []);
 
   useEffect(() => {
     completeZkLogin();
@@ -486,4 +526,23 @@ function App() {
   );
 }
 
+/* Explored tiles */
+
+/**
+ * Base64 of `bytes`, converted a slice at a time: spreading a whole save into
+ * `String.fromCharCode` can exceed the engine's argument limit.
+ */
+function encodeBytes(bytes: Uint8Array): string {
+    const SLICE = 0x8000;
+    let binary = "";
+    for (let i = 0; i < bytes.length; i += SLICE) {
+        binary += String.fromCharCode(...bytes.subarray(i, i + SLICE));
+    }
+    return btoa(binary);
+}
+
+function decodeBytes(base64: string): Uint8Array {
+    return Uint8Array.from(atob(base64), (c) => c.charCodeAt(0));
+}
+
 export default App;
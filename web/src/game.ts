@@ -39,6 +39,13 @@ export function GameProgramID(): string {
     return GAME_PROGRAM;
 }
 
+export async function GetRegisteredPlayers(client: SuiClient): Promise<string[]> {
+    const obj = await GetGameObject(client);
+    // `showContent` puts every Move struct's fields under `fields`: `Game.players` is a
+    // `Registry`, whose `players` is a `VecSet<address>` with the addresses in `contents`.
+    return obj.fields.players.fields.players.fields.contents;
+}
+
 export async function IsUserPartOfGame(client: SuiClient, address: String): Promise<Boolean> {
     const obj = await GetGameObject(client);
     let players = obj.fields.players;